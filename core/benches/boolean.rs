@@ -17,12 +17,24 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         let mut rects = gen_rects(&mut rng, view, 100);
         rects.push(Rect::new(view / 5.0, view / 5.0));
 
-        let mut sx = SimplicalChain::default();
-        for r in rects {
-            let p = r.polygon(1);
-            let sx_r = SimplicalChain::from_polygon(&p);
-            sx = sx.union(&sx_r);
-        }
+        c.bench_function("SimplicalChain::union fold", |b| {
+            b.iter(|| {
+                let mut sx = SimplicalChain::default();
+                for r in &rects {
+                    sx = sx.union(&SimplicalChain::from_polygon(&r.polygon(1)));
+                }
+                sx
+            })
+        });
+
+        let chains = rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(1)))
+            .collect::<Vec<_>>();
+        c.bench_function("SimplicalChain::union_all", |b| {
+            b.iter(|| SimplicalChain::union_all(chains.clone()))
+        });
+        let sx = SimplicalChain::union_all(chains);
 
         c.bench_function("build_net", |b| b.iter(|| build_net(view, &sx, true)));
 
@@ -132,6 +144,24 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             |b| b.iter(|| s0.intersect(&s1)),
         );
     }
+
+    // thousands of edges on each side
+    for count in [100, 500] {
+        let view = 100.0;
+        let mut sx = [SimplicalChain::default(), SimplicalChain::default()];
+        for sx in &mut sx {
+            for r in gen_rects(&mut rng, view, count) {
+                sx.simplices
+                    .extend(SimplicalChain::from_polygon(&r.polygon(4)).simplices);
+            }
+        }
+        let [s0, s1] = sx;
+
+        c.bench_function(
+            &format!("SimplicalChain::subdivide_prepare rects {count}"),
+            |b| b.iter(|| s0.subdivide_prepare(&s1)),
+        );
+    }
 }
 
 criterion_group!(benches, criterion_benchmark);
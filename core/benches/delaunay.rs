@@ -1,3 +1,19 @@
+// Notes on the compact storage of triangles, 24 bytes per triangle instead of 72. Medians of
+// `cargo bench --bench delaunay -- --warm-up-time 1 --measurement-time 3`, before -> after:
+//
+//   dalaunay 20 / 100 / 1000             9.44us / 60.6us / 1.38ms -> 8.33us / 60.3us / 1.36ms
+//   divide-and-conquer 20 / 100 / 1000   38.5us / 357us / 4.87ms  -> 40.6us / 375us / 4.97ms
+//   dalaunay rect 3x3 / 10x10 / 30x30    3.59us / 97.4us / 2.57ms -> 4.09us / 104us / 2.46ms
+//   locate 10 / 100 / 1000               16.6 / 17.9 / 19.3ns     -> 16.4 / 14.4 / 16.7ns
+//   locate_recursive 10 / 100 / 1000     113 / 430 / 89.6ns       -> 105 / 416 / 81.5ns
+//   cut 10 / 100 / 1000                  849ns / 2.41us / 5.75us  -> 923ns / 2.49us / 6.41us
+//   cut_apply 10 / 100 / 1000            862ns / 2.95us / 20.4us  -> 944ns / 2.06us / 9.23us
+//   clone 10 / 100 / 1000                182ns / 844ns / 13.7us   -> 99.8ns / 422ns / 3.07us
+//
+// Point location and cuts read single vertices and neighbors of a triangle, without decoding
+// the whole triangle, so insertion is within noise. `cut` is up to 12% slower, since it still
+// decodes the crossed triangles, and is paid back by `cut_apply` and `clone`.
+
 use core::{
     delaunay::{TriIdx, TriangularNetwork, VertIdx},
     points_grid, points_uniform,
@@ -30,6 +46,18 @@ fn gen_delaunay(view: f64, points: &[Point<f64>]) -> TriangularNetwork<f64> {
     t
 }
 
+fn gen_delaunay_dc(view: f64, points: &[Point<f64>]) -> TriangularNetwork<f64> {
+    let v = view * 4.0;
+    let (t, _) = TriangularNetwork::from_points(
+        Point::new([-v, -v]),
+        Point::new([v, -v]),
+        Point::new([0.0, v]),
+        points,
+    )
+    .unwrap();
+    t
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
     let seed: <ChaCha20Rng as SeedableRng>::Seed = Default::default();
     let mut rng = ChaCha20Rng::from_seed(seed);
@@ -43,6 +71,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         c.bench_function(&format!("dalaunay {i}"), |b| {
             b.iter(|| gen_delaunay(view, &points))
         });
+        c.bench_function(&format!("dalaunay divide-and-conquer {i}"), |b| {
+            b.iter(|| gen_delaunay_dc(view, &points))
+        });
     }
 
     for size in [10, 100, 1000] {
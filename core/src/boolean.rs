@@ -1,25 +1,69 @@
 // https://citeseerx.ist.psu.edu/viewdoc/download?doi=10.1.1.83.6811&rep=rep1&type=pdf
 
 use crate::aabb::AABB;
+use crate::rings::crossing;
+use crate::snap::Snap;
 use rgeometry::{data::*, Intersects, Orientation, PolygonScalar};
+use std::collections::{HashMap, HashSet};
+
+/// Boolean operation between two chains, `a` and `b` in `a.boolean(&b, op)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// `a | b`
+    Union,
+    /// `a & b`
+    Intersection,
+    /// `a - b`
+    Difference,
+    /// `b - a`
+    ReverseDifference,
+    /// `a ^ b`, area covered by exactly one of the chains
+    Xor,
+}
+
+/// Rule deciding which areas of a chain are inside, from their winding numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// odd winding numbers
+    EvenOdd,
+    /// winding numbers other than zero
+    NonZero,
+    /// positive winding numbers, areas of counterclockwise boundaries
+    Positive,
+    /// negative winding numbers, areas of clockwise boundaries
+    Negative,
+}
+
+impl FillRule {
+    /// Area with the winding number is inside
+    pub fn filled(&self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0,
+            FillRule::Positive => winding > 0,
+            FillRule::Negative => winding < 0,
+        }
+    }
+}
 
 #[derive(Clone, Default, Debug, PartialEq)]
 pub struct SimplicalChain<T: PolygonScalar> {
     pub simplices: Vec<Simplex<T>>,
 }
 
-fn aabb_intersect<T: PolygonScalar + Clone>(
-    p0: &Point<T>,
-    p1: &Point<T>,
-    q0: &Point<T>,
-    q1: &Point<T>,
-) -> bool {
-    let mut aabb_p = AABB::new(p0);
-    aabb_p.extend(p1);
-    let mut aabb_q = AABB::new(q0);
-    aabb_q.extend(q1);
+// bounding box of the simplex
+fn simplex_aabb<T: PolygonScalar + Clone>(s: &Simplex<T>) -> AABB<T> {
+    let mut aabb = AABB::new(&s.src);
+    aabb.extend(&s.dst);
+    aabb
+}
 
-    aabb_p.interects(&aabb_q)
+// no point in common, including the sides
+fn aabb_disjoint<T: PolygonScalar>(a: &AABB<T>, b: &AABB<T>) -> bool {
+    a.max.array[0] < b.min.array[0]
+        || b.max.array[0] < a.min.array[0]
+        || a.max.array[1] < b.min.array[1]
+        || b.max.array[1] < a.min.array[1]
 }
 
 fn intersection_point<T: PolygonScalar + Clone + std::fmt::Debug>(
@@ -73,39 +117,50 @@ impl<T: PolygonScalar + Clone> SimplicalChain<T> {
         return sum;
     }
 
+    /// Intersections between simplices of `self` and `other`, as `(index in self, index in
+    /// other, point)`, ordered by the indices. Overlapping simplices give both ends of the
+    /// overlap. Candidate pairs are found by sweeping bounding boxes along the x axis, so only
+    /// simplices with overlapping x ranges are compared.
     pub fn subdivide_prepare(&self, other: &SimplicalChain<T>) -> Vec<(usize, usize, Point<T>)> {
+        let chains = [self, other];
+        let aabbs = chains.map(|sx| sx.simplices.iter().map(simplex_aabb).collect::<Vec<_>>());
+
+        // (chain, simplex), ordered by the left side of the bounding box
+        let mut order = (0..self.simplices.len())
+            .map(|i| (0, i))
+            .chain((0..other.simplices.len()).map(|i| (1, i)))
+            .collect::<Vec<_>>();
+        order.sort_by(|(c0, i0), (c1, i1)| {
+            let x0 = &aabbs[*c0][*i0].min.array[0];
+            let x1 = &aabbs[*c1][*i1].min.array[0];
+            x0.partial_cmp(x1).unwrap()
+        });
+
         let mut intersections = Vec::new();
+        let mut active: [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+        for (c, i) in order {
+            let aabb = &aabbs[c][i];
+            let x = &aabb.min.array[0];
+            for (active, aabbs) in active.iter_mut().zip(&aabbs) {
+                active.retain(|j| aabbs[*j].max.array[0] >= *x);
+            }
 
-        for (i0, s0) in self.simplices.iter().enumerate() {
-            let l0 = LineSegment::new(
-                EndPoint::Exclusive(s0.src.clone()),
-                EndPoint::Exclusive(s0.dst.clone()),
-            );
-            for (i1, s1) in other.simplices.iter().enumerate() {
-                if !aabb_intersect(&s0.src, &s0.dst, &s1.src, &s1.dst) {
+            for j in &active[1 - c] {
+                let aabb_j = &aabbs[1 - c][*j];
+                if aabb_j.max.array[1] < aabb.min.array[1]
+                    || aabb.max.array[1] < aabb_j.min.array[1]
+                {
                     continue;
                 }
-                let l1 = LineSegment::new(
-                    EndPoint::Exclusive(s1.src.clone()),
-                    EndPoint::Exclusive(s1.dst.clone()),
-                );
-                match l0.intersect(&l1) {
-                    Some(ILineSegment::Crossing) => {
-                        if let Some(p) = intersection_point(&s0.src, &s0.dst, &s1.src, &s1.dst) {
-                            intersections.push((i0, i1, p));
-                        }
-                    }
-                    Some(ILineSegment::Overlap(view)) => {
-                        // eprintln!("overlap {:?} / {:?} / {:?}", l0, l1, view);
-                        let p = view.min.take();
-                        intersections.push((i0, i1, p.clone()));
-                        let p = view.max.take();
-                        intersections.push((i0, i1, p.clone()));
-                    }
-                    _ => (),
-                }
+                let (i0, i1) = if c == 0 { (i, *j) } else { (*j, i) };
+                self.simplices[i0]
+                    .intersections(&other.simplices[i1], |p| intersections.push((i0, i1, p)));
             }
+            active[c].push(i);
         }
+
+        // stable, keeps both ends of an overlap in order
+        intersections.sort_by_key(|(i0, i1, _)| (*i0, *i1));
         intersections
     }
 
@@ -116,14 +171,16 @@ impl<T: PolygonScalar + Clone> SimplicalChain<T> {
     ) -> SimplicalChain<T> {
         let mut simplices = Vec::new();
 
+        // intersection points of each simplex
+        let mut points = vec![Vec::new(); self.simplices.len()];
+        for (idx0, idx1, p) in intersections {
+            let idx = if is_first { *idx0 } else { *idx1 };
+            points[idx].push(p.clone());
+        }
+
         for (i0, s0) in self.simplices.iter().enumerate() {
             let mut intersection_points = vec![s0.src.clone(), s0.dst.clone()];
-
-            for (idx0, idx1, p) in intersections {
-                if (is_first && *idx0 == i0) || (!is_first && *idx1 == i0) {
-                    intersection_points.push(p.clone());
-                }
-            }
+            intersection_points.append(&mut points[i0]);
 
             if intersection_points.len() > 2 {
                 intersection_points.sort_by(|a, b| s0.src.cmp_distance_to(a, b));
@@ -162,49 +219,35 @@ impl<T: PolygonScalar + Clone> SimplicalChain<T> {
         let sx1_subdivide = sx1.subdivide(&intersections, false);
 
         // calculate edge characteristics
+        let edges = EdgeSet::new(&sx0_subdivide, &sx1_subdivide);
+        let winding0 = WindingIndex::new(&sx0.simplices);
+        let winding1 = WindingIndex::new(&sx1.simplices);
 
         // f(s_i*, P2)
         let mut ec_sx0 = Vec::with_capacity(sx0_subdivide.simplices.len());
-        ec_sx0.resize(sx0_subdivide.simplices.len(), 0f64);
+        for s in &sx0_subdivide.simplices {
+            let ec = if edges.contains(1, s) {
+                1.0
+            } else if edges.contains(1, &s.reverse()) {
+                0.0
+            } else {
+                winding1.winding(&s.midpoint()) as f64
+            };
+            ec_sx0.push(ec);
+        }
 
         // f(u_i*, P1)
         let mut ec_sx1 = Vec::with_capacity(sx1_subdivide.simplices.len());
-        ec_sx1.resize(sx1_subdivide.simplices.len(), 0f64);
-
-        for (idx0, s) in sx0_subdivide.simplices.iter().enumerate() {
-            if sx1_subdivide.simplices.contains(&s) {
-                ec_sx0[idx0] = 1.0;
-                continue;
-            }
-            if sx1_subdivide.simplices.contains(&s.reverse()) {
-                continue;
-            }
-
-            let q = s.midpoint();
-            for u in &sx1.simplices {
-                ec_sx0[idx0] += u.beta(&q);
-            }
-            // eprintln!("idx={}, c={}", idx0, ec_sx0[idx0]);
+        for u in &sx1_subdivide.simplices {
+            let ec = if edges.contains(0, u) || edges.contains(0, &u.reverse()) {
+                0.0
+            } else {
+                winding0.winding(&u.midpoint()) as f64
+            };
+            ec_sx1.push(ec);
         }
 
-        for (idx1, u) in sx1_subdivide.simplices.iter().enumerate() {
-            let u_in_f1 = sx0_subdivide.simplices.contains(&u);
-            let u_in_f2 = sx0_subdivide.simplices.contains(&u.reverse());
-
-            if u_in_f1 || u_in_f2 {
-                continue;
-            }
-
-            let q = u.midpoint();
-            for s in &sx0.simplices {
-                ec_sx1[idx1] += s.beta(&q);
-            }
-            // eprintln!("idx={}, c={}", idx1, ec_sx1[idx1]);
-        }
-        // eprintln!("0 {:?} {:?}", sx0_subdivide, ec_sx0);
-        // eprintln!("1 {:?} {:?}", sx1_subdivide, ec_sx1);
-
-        return (sx0_subdivide, ec_sx0, sx1_subdivide, ec_sx1);
+        (sx0_subdivide, ec_sx0, sx1_subdivide, ec_sx1)
     }
 
     pub fn run(
@@ -238,16 +281,346 @@ impl<T: PolygonScalar + Clone> SimplicalChain<T> {
         SimplicalChain { simplices }
     }
 
+    /// Apply the boolean operation, with `self` as the first operand. For `f64` chains,
+    /// `boolean_snapped` snap rounds both operands first.
+    pub fn boolean(&self, other: &SimplicalChain<T>, op: BooleanOp) -> SimplicalChain<T> {
+        match op {
+            BooleanOp::Union => self.run(other, true, false, true, false),
+            BooleanOp::Intersection => self.run(other, false, false, false, false),
+            BooleanOp::Difference => self.run(other, true, false, false, true),
+            BooleanOp::ReverseDifference => other.run(self, true, false, false, true),
+            BooleanOp::Xor => self.xor(other),
+        }
+    }
+
+    // every simplex is on the boundary of the symmetric difference, reversed if it is inside
+    // of the other chain. shared simplices bound both sides of a zero area, and cancel out
+    fn xor(&self, other: &SimplicalChain<T>) -> SimplicalChain<T> {
+        let (sx0_subdivide, ec_sx0, sx1_subdivide, ec_sx1) = self.subdivide_chracteristics(other);
+
+        let simplices = sx0_subdivide
+            .simplices
+            .into_iter()
+            .zip(ec_sx0)
+            .chain(sx1_subdivide.simplices.into_iter().zip(ec_sx1))
+            .map(|(s, ec)| if ec == 1.0 { s.reverse() } else { s })
+            .collect::<Vec<_>>();
+
+        SimplicalChain { simplices }.cancel_opposite()
+    }
+
+    // drop pairs of opposite simplices
+    pub(crate) fn cancel_opposite(self) -> SimplicalChain<T> {
+        let empty = SimplicalChain { simplices: vec![] };
+        let edges = EdgeSet::new(&self, &empty);
+        let mut open = HashMap::<(usize, usize), Vec<usize>>::new();
+        let mut keep = vec![true; self.simplices.len()];
+        for (idx, s) in self.simplices.iter().enumerate() {
+            let (src, dst) = edges.key(s).unwrap();
+            if let Some(opposite) = open.get_mut(&(dst, src)).and_then(|v| v.pop()) {
+                keep[opposite] = false;
+                keep[idx] = false;
+                continue;
+            }
+            open.entry((src, dst)).or_default().push(idx);
+        }
+
+        let simplices = self
+            .simplices
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(s, keep)| keep.then_some(s))
+            .collect();
+        SimplicalChain { simplices }
+    }
+
+    /// Chain bounding the areas filled under `fill_rule`, for chains which overlap themselves
+    /// or mix orientations. Simplices are split where they cross or overlap, copies of the same
+    /// simplex are merged, and simplices between a filled and an unfilled area are kept, with
+    /// the filled area on the left. The result is a simple, consistently oriented input for the
+    /// boolean operations.
+    pub fn normalize(&self, fill_rule: FillRule) -> SimplicalChain<T> {
+        let simplices = self
+            .simplices
+            .iter()
+            .filter(|s| s.src != s.dst)
+            .cloned()
+            .collect();
+        let sx = SimplicalChain { simplices };
+        let intersections = sx.subdivide_prepare(&sx);
+        let sx = sx.subdivide(&intersections, true);
+
+        // copies of each simplex, counted along the first one, and negated if reversed
+        let empty = SimplicalChain { simplices: vec![] };
+        let edges = EdgeSet::new(&sx, &empty);
+        let mut groups = Vec::<(usize, i32)>::new();
+        let mut group_of = HashMap::<(usize, usize), usize>::new();
+        for (idx, s) in sx.simplices.iter().enumerate() {
+            let (src, dst) = edges.key(s).unwrap();
+            if let Some(group) = group_of.get(&(src, dst)) {
+                groups[*group].1 += 1;
+            } else if let Some(group) = group_of.get(&(dst, src)) {
+                groups[*group].1 -= 1;
+            } else {
+                group_of.insert((src, dst), groups.len());
+                groups.push((idx, 1));
+            }
+        }
+
+        let winding = WindingIndex::new(&sx.simplices);
+        let mut simplices = Vec::new();
+        for (idx, count) in groups {
+            let s = &sx.simplices[idx];
+            let mid = s.midpoint();
+
+            // `s` and its copies pass through `mid` and do not cross the ray to the right of it, so
+            // the ray sees the winding number of the right side of `s`, or of the upper side if
+            // `s` is horizontal
+            let rest = winding.winding(&mid);
+            let [x0, y0] = &s.src.array;
+            let [x1, y1] = &s.dst.array;
+            let rest_on_left = y1 < y0 || (y1 == y0 && x0 < x1);
+            let (left, right) = if rest_on_left {
+                (rest, rest - count)
+            } else {
+                (rest + count, rest)
+            };
+
+            match (fill_rule.filled(left), fill_rule.filled(right)) {
+                (true, false) => simplices.push(s.clone()),
+                (false, true) => simplices.push(s.reverse()),
+                _ => {}
+            }
+        }
+        SimplicalChain { simplices }
+    }
+
+    /// Bounding box of the chain, or `None` if the chain is empty
+    pub fn aabb(&self) -> Option<AABB<T>> {
+        let mut iter = self.simplices.iter();
+        let mut aabb = simplex_aabb(iter.next()?);
+        for s in iter {
+            aabb.extend_aabb(&simplex_aabb(s));
+        }
+        Some(aabb)
+    }
+
+    /// Union of all chains. Chains are merged pairwise in a balanced tree, so intermediate
+    /// chains stay small. Chains with disjoint bounding boxes are merged without subdividing.
+    pub fn union_all(chains: impl IntoIterator<Item = SimplicalChain<T>>) -> SimplicalChain<T> {
+        let mut level = chains
+            .into_iter()
+            .filter_map(|sx| Some((sx.aabb()?, sx)))
+            .collect::<Vec<_>>();
+
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len().div_ceil(2));
+            let mut iter = level.into_iter();
+            while let Some((aabb0, sx0)) = iter.next() {
+                let (aabb1, sx1) = match iter.next() {
+                    Some(other) => other,
+                    None => {
+                        next.push((aabb0, sx0));
+                        break;
+                    }
+                };
+
+                let sx = if aabb_disjoint(&aabb0, &aabb1) {
+                    let mut simplices = sx0.simplices;
+                    simplices.extend(sx1.simplices);
+                    SimplicalChain { simplices }
+                } else {
+                    sx0.union(&sx1)
+                };
+                let mut aabb = aabb0;
+                aabb.extend_aabb(&aabb1);
+                next.push((aabb, sx));
+            }
+            level = next;
+        }
+
+        match level.pop() {
+            Some((_, sx)) => sx,
+            None => SimplicalChain { simplices: vec![] },
+        }
+    }
+
     pub fn intersect(&self, other: &SimplicalChain<T>) -> SimplicalChain<T> {
-        self.run(other, false, false, false, false)
+        self.boolean(other, BooleanOp::Intersection)
     }
 
     pub fn union(&self, other: &SimplicalChain<T>) -> SimplicalChain<T> {
-        self.run(other, true, false, true, false)
+        self.boolean(other, BooleanOp::Union)
     }
 
     pub fn subtract(&self, other: &SimplicalChain<T>) -> SimplicalChain<T> {
-        self.run(other, true, false, false, true)
+        self.boolean(other, BooleanOp::Difference)
+    }
+}
+
+impl SimplicalChain<f64> {
+    // both chains, snap rounded together
+    fn snap_pair(&self, other: &SimplicalChain<f64>, snap: Snap) -> [SimplicalChain<f64>; 2] {
+        let spacing = match snap.spacing() {
+            Some(spacing) => spacing,
+            None => return [self.clone(), other.clone()],
+        };
+        let mut chains = SimplicalChain::snap_round(&[self.clone(), other.clone()], spacing);
+        let sx1 = chains.pop().unwrap();
+        let sx0 = chains.pop().unwrap();
+        [sx0, sx1]
+    }
+
+    /// `run`, with both chains snap rounded together by `snap` first, see
+    /// `SimplicalChain::snap_round`. Rounded intersection points of nearly collinear simplices
+    /// make the winding tests of `run` inconsistent in `f64`, while snap rounded chains only
+    /// meet at shared vertices. `Snap::Exact` is the same as `run`. Snap rounding needs `f64`
+    /// coordinates, while `run` is generic over the scalar and exact for rationals, so snapping
+    /// is a separate entry point rather than an option of `run`.
+    pub fn run_snapped(
+        &self,
+        other: &SimplicalChain<f64>,
+        include_sx0: bool,
+        rev_sx0: bool,
+        include_sx1: bool,
+        rev_sx1: bool,
+        snap: Snap,
+    ) -> SimplicalChain<f64> {
+        let [sx0, sx1] = self.snap_pair(other, snap);
+        sx0.run(&sx1, include_sx0, rev_sx0, include_sx1, rev_sx1)
+    }
+
+    /// `boolean`, with both chains snap rounded together by `snap` first
+    pub fn boolean_snapped(
+        &self,
+        other: &SimplicalChain<f64>,
+        op: BooleanOp,
+        snap: Snap,
+    ) -> SimplicalChain<f64> {
+        let [sx0, sx1] = self.snap_pair(other, snap);
+        sx0.boolean(&sx1, op)
+    }
+}
+
+// simplices of two chains, hashed by the ids of their end points
+struct EdgeSet<T: PolygonScalar> {
+    // distinct points of both chains, sorted
+    points: Vec<Point<T>>,
+    edges: [HashSet<(usize, usize)>; 2],
+}
+
+impl<T: PolygonScalar + Clone> EdgeSet<T> {
+    fn new(sx0: &SimplicalChain<T>, sx1: &SimplicalChain<T>) -> Self {
+        let mut points = [sx0, sx1]
+            .iter()
+            .flat_map(|sx| sx.simplices.iter())
+            .flat_map(|s| [s.src.clone(), s.dst.clone()])
+            .collect::<Vec<_>>();
+        points.sort_by(|a, b| a.array.partial_cmp(&b.array).unwrap());
+        points.dedup();
+
+        let mut set = Self {
+            points,
+            edges: [HashSet::new(), HashSet::new()],
+        };
+        for (chain, sx) in [sx0, sx1].iter().enumerate() {
+            for s in &sx.simplices {
+                let key = set.key(s).unwrap();
+                set.edges[chain].insert(key);
+            }
+        }
+        set
+    }
+
+    fn point_id(&self, p: &Point<T>) -> Option<usize> {
+        let idx = self.points.partition_point(|q| q.array < p.array);
+        match self.points.get(idx) {
+            Some(q) if q == p => Some(idx),
+            _ => None,
+        }
+    }
+
+    fn key(&self, s: &Simplex<T>) -> Option<(usize, usize)> {
+        Some((self.point_id(&s.src)?, self.point_id(&s.dst)?))
+    }
+
+    // `s` is a simplex of the chain
+    fn contains(&self, chain: usize, s: &Simplex<T>) -> bool {
+        match self.key(s) {
+            Some(key) => self.edges[chain].contains(&key),
+            None => false,
+        }
+    }
+}
+
+// simplices of a chain in a segment tree over their y ranges, to count the simplices crossed
+// by a horizontal ray
+struct WindingIndex<'a, T: PolygonScalar> {
+    simplices: &'a [Simplex<T>],
+    // distinct y coordinates, sorted. leaves of the tree
+    ys: Vec<T>,
+    // simplices covering the whole range of each node, the root at 1
+    nodes: Vec<Vec<usize>>,
+    size: usize,
+}
+
+impl<'a, T: PolygonScalar + Clone> WindingIndex<'a, T> {
+    fn new(simplices: &'a [Simplex<T>]) -> Self {
+        let mut ys = simplices
+            .iter()
+            .flat_map(|s| [s.src.array[1].clone(), s.dst.array[1].clone()])
+            .collect::<Vec<_>>();
+        ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ys.dedup();
+
+        let size = ys.len().next_power_of_two();
+        let mut index = Self {
+            simplices,
+            ys,
+            nodes: vec![Vec::new(); size * 2],
+            size,
+        };
+
+        for (idx, s) in simplices.iter().enumerate() {
+            let rank = |y: &T| index.ys.partition_point(|v| v < y);
+            let (r0, r1) = (rank(&s.src.array[1]), rank(&s.dst.array[1]));
+            let (mut lo, mut hi) = (r0.min(r1) + size, r0.max(r1) + size + 1);
+            while lo < hi {
+                if lo & 1 == 1 {
+                    index.nodes[lo].push(idx);
+                    lo += 1;
+                }
+                if hi & 1 == 1 {
+                    hi -= 1;
+                    index.nodes[hi].push(idx);
+                }
+                lo /= 2;
+                hi /= 2;
+            }
+        }
+        index
+    }
+
+    // winding number of the chain around `q`, see `rings::crossing`. simplices through `q` do
+    // not count
+    fn winding(&self, q: &Point<T>) -> i32 {
+        let y = &q.array[1];
+        let rank = self.ys.partition_point(|v| v < y);
+        if rank == self.ys.len() {
+            return 0;
+        }
+
+        // simplices covering the leaf, from the leaf to the root
+        let mut winding = 0;
+        let mut node = rank + self.size;
+        while node > 0 {
+            for idx in &self.nodes[node] {
+                let s = &self.simplices[*idx];
+                winding += crossing(&s.src, &s.dst, q);
+            }
+            node /= 2;
+        }
+        winding
     }
 }
 
@@ -275,6 +648,56 @@ impl<T: PolygonScalar> Simplex<T> {
 }
 
 impl<T: PolygonScalar + Clone> Simplex<T> {
+    // crossing point, or both ends of the overlap, excluding shared end points
+    fn intersections(&self, other: &Simplex<T>, mut f: impl FnMut(Point<T>)) {
+        let l0 = LineSegment::new(
+            EndPoint::Exclusive(self.src.clone()),
+            EndPoint::Exclusive(self.dst.clone()),
+        );
+        let l1 = LineSegment::new(
+            EndPoint::Exclusive(other.src.clone()),
+            EndPoint::Exclusive(other.dst.clone()),
+        );
+        match l0.intersect(&l1) {
+            Some(ILineSegment::Crossing) => {
+                if let Some(p) = intersection_point(&self.src, &self.dst, &other.src, &other.dst) {
+                    f(p);
+                }
+            }
+            Some(ILineSegment::Overlap(view)) => {
+                f(view.min.take().clone());
+                f(view.max.take().clone());
+            }
+            _ => {
+                // an end point touching the inside of the other simplex
+                for p in [&other.src, &other.dst] {
+                    if self.contains_inner(p) {
+                        f(p.clone());
+                    }
+                }
+                for p in [&self.src, &self.dst] {
+                    if other.contains_inner(p) {
+                        f(p.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // `p` is on the simplex, excluding the end points
+    fn contains_inner(&self, p: &Point<T>) -> bool {
+        if *p == self.src || *p == self.dst {
+            return false;
+        }
+        let o = Point::orient_along_direction(&self.src, Direction::Through(&self.dst), p);
+        let aabb = simplex_aabb(self);
+        o == Orientation::CoLinear
+            && aabb.min.array[0] <= p.array[0]
+            && p.array[0] <= aabb.max.array[0]
+            && aabb.min.array[1] <= p.array[1]
+            && p.array[1] <= aabb.max.array[1]
+    }
+
     // clockwise = -1
     // counterclockwise = 1
     // colinear = 0
@@ -315,6 +738,7 @@ impl<T: PolygonScalar + Clone> Simplex<T> {
     }
 
     // assume that p is not on non-original edge of the simplex
+    #[cfg(test)]
     fn beta(&self, q: &Point<T>) -> f64 {
         self.beta0(q).unwrap_or(0.0)
     }
@@ -602,6 +1026,268 @@ mod test {
         assert_eq!(subdivide.simplices.len(), 6);
     }
 
+    #[test]
+    fn subdivide_prepare_all_pairs() {
+        use crate::gen_rects;
+        use rand::prelude::*;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut s0 = SimplicalChain::default();
+        for r in gen_rects(&mut rng, 30.0, 20) {
+            s0 = s0.union(&SimplicalChain::from_polygon(&r.polygon(1)));
+        }
+        let mut s1 = SimplicalChain::default();
+        for r in gen_rects(&mut rng, 30.0, 20) {
+            s1 = s1.union(&SimplicalChain::from_polygon(&r.polygon(2)));
+        }
+
+        let mut expected = Vec::new();
+        for (i0, u) in s0.simplices.iter().enumerate() {
+            for (i1, v) in s1.simplices.iter().enumerate() {
+                u.intersections(v, |p| expected.push((i0, i1, p)));
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(s0.subdivide_prepare(&s1), expected);
+    }
+
+    #[test]
+    fn subdivide_chracteristics_winding() {
+        use crate::gen_rects;
+        use rand::prelude::*;
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut s0 = SimplicalChain::default();
+        for r in gen_rects(&mut rng, 30.0, 20) {
+            s0 = s0.union(&SimplicalChain::from_polygon(&r.polygon(1)));
+        }
+        let mut s1 = SimplicalChain::default();
+        for r in gen_rects(&mut rng, 30.0, 20) {
+            s1 = s1.union(&SimplicalChain::from_polygon(&r.rot(0.3).polygon(1)));
+        }
+
+        // sum of `beta` over the original simplices, and the edge lookup by a linear scan
+        let (sx0, ec0, sx1, ec1) = s0.subdivide_chracteristics(&s1);
+        for (s, ec) in sx0.simplices.iter().zip(&ec0) {
+            let expected = if sx1.simplices.contains(s) {
+                1.0
+            } else if sx1.simplices.contains(&s.reverse()) {
+                0.0
+            } else {
+                s1.simplices.iter().map(|u| u.beta(&s.midpoint())).sum()
+            };
+            assert_eq!(*ec, expected);
+        }
+        for (u, ec) in sx1.simplices.iter().zip(&ec1) {
+            let expected = if sx0.simplices.contains(u) || sx0.simplices.contains(&u.reverse()) {
+                0.0
+            } else {
+                s0.simplices.iter().map(|s| s.beta(&u.midpoint())).sum()
+            };
+            assert_eq!(*ec, expected);
+        }
+        assert!(ec0.contains(&1.0));
+    }
+
+    #[test]
+    fn boolean_ops() {
+        let s0 = SimplicalChain::from_polygon(&polygon_cube(Point::new([0.0, 0.0]), 2.0));
+        let s1 = SimplicalChain::from_polygon(&polygon_cube(Point::new([2.0, 2.0]), 2.0));
+
+        // 16 each, overlapping by 4
+        assert_eq!(s0.boolean(&s1, BooleanOp::Union).signed_area(), 28.0);
+        assert_eq!(s0.boolean(&s1, BooleanOp::Intersection).signed_area(), 4.0);
+        assert_eq!(s0.boolean(&s1, BooleanOp::Difference).signed_area(), 12.0);
+        assert_eq!(
+            s0.boolean(&s1, BooleanOp::ReverseDifference).signed_area(),
+            12.0
+        );
+        assert_eq!(s0.boolean(&s1, BooleanOp::Xor).signed_area(), 24.0);
+
+        let rev = s0.boolean(&s1, BooleanOp::ReverseDifference);
+        let sub = s1.subtract(&s0);
+        assert_eq!(rev.simplices.len(), sub.simplices.len());
+        assert!(rev.simplices.iter().all(|s| sub.simplices.contains(s)));
+
+        let xor = s0.boolean(&s1, BooleanOp::Xor);
+        assert!(xor.rings().is_ok());
+        assert_eq!(xor.rings().unwrap().len(), 2);
+
+        // identical chains cancel out
+        assert!(s0.boolean(&s0, BooleanOp::Xor).simplices.is_empty());
+    }
+
+    #[test]
+    fn boolean_reverse_shared_side() {
+        // [0, 2]^2 and [0, 4]x[0, 2] share the left side and parts of the others, in the same
+        // direction
+        let s0 = SimplicalChain::from_polygon(&polygon_cube(Point::new([1.0, 1.0]), 1.0));
+        let p1 = Polygon::new(
+            [[0.0, 0.0], [4.0, 0.0], [4.0, 2.0], [0.0, 2.0]]
+                .map(Point::new)
+                .to_vec(),
+        )
+        .unwrap();
+        let s1 = SimplicalChain::from_polygon(&p1);
+
+        let rev = s0.boolean(&s1, BooleanOp::ReverseDifference);
+        let sub = s1.subtract(&s0);
+        assert_eq!(rev.simplices.len(), sub.simplices.len());
+        assert!(rev.simplices.iter().all(|s| sub.simplices.contains(s)));
+
+        let rings = rev.rings().unwrap();
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 4);
+    }
+
+    #[test]
+    fn normalize_fill_rules() {
+        use crate::measure::Location;
+        use FillRule::*;
+
+        let chain = |points: &[[f64; 2]]| {
+            let simplices = (0..points.len())
+                .map(|i| Simplex {
+                    src: Point::new(points[i]),
+                    dst: Point::new(points[(i + 1) % points.len()]),
+                })
+                .collect();
+            SimplicalChain { simplices }
+        };
+
+        // bow tie, the left triangle is counterclockwise and the right one clockwise
+        let sx = chain(&[[0.0, 0.0], [2.0, 2.0], [2.0, 0.0], [0.0, 2.0]]);
+        assert_eq!(sx.normalize(NonZero).signed_area(), 2.0);
+        assert_eq!(sx.normalize(EvenOdd).signed_area(), 2.0);
+        let positive = sx.normalize(Positive);
+        assert_eq!(positive.signed_area(), 1.0);
+        assert_eq!(positive.locate(&Point::new([0.5, 1.0])), Location::Inside);
+        let negative = sx.normalize(Negative);
+        assert_eq!(negative.signed_area(), 1.0);
+        assert_eq!(negative.locate(&Point::new([1.5, 1.0])), Location::Inside);
+
+        // overlapping squares, the overlap is covered twice
+        let s0 = SimplicalChain::from_polygon(&polygon_cube(Point::new([0.0, 0.0]), 2.0));
+        let s1 = SimplicalChain::from_polygon(&polygon_cube(Point::new([2.0, 2.0]), 2.0));
+        let mut sx = s0.clone();
+        sx.simplices.extend(s1.simplices.iter().cloned());
+        assert_eq!(sx.normalize(NonZero).signed_area(), 28.0);
+        assert_eq!(sx.normalize(EvenOdd).signed_area(), 24.0);
+        assert!(sx.normalize(Negative).simplices.is_empty());
+        assert!(sx.normalize(NonZero).rings().is_ok());
+
+        // the second square reversed makes a hole, except for the positive rule
+        let mut sx = s0.clone();
+        sx.simplices
+            .extend(s1.simplices.iter().map(|s| s.reverse()));
+        assert_eq!(sx.normalize(NonZero).signed_area(), 24.0);
+        assert_eq!(sx.normalize(Positive).signed_area(), 12.0);
+        assert_eq!(sx.normalize(Negative).signed_area(), 12.0);
+
+        // duplicated boundary
+        let mut sx = s0.clone();
+        sx.simplices.extend(s0.simplices.iter().cloned());
+        assert_eq!(sx.normalize(NonZero).simplices.len(), 4);
+        assert!(sx.normalize(EvenOdd).simplices.is_empty());
+
+        // a clean chain is unchanged
+        assert_eq!(s0.normalize(NonZero), s0);
+    }
+
+    #[test]
+    fn union_all() {
+        use crate::gen_rects;
+        use rand::prelude::*;
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let rects = gen_rects(&mut rng, 30.0, 40);
+        let chains = rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(1)))
+            .collect::<Vec<_>>();
+
+        let mut folded = SimplicalChain::default();
+        for sx in &chains {
+            folded = folded.union(sx);
+        }
+        let sx = SimplicalChain::union_all(chains);
+        assert!((sx.signed_area() - folded.signed_area()).abs() < 1e-6);
+        assert_eq!(sx.rings().unwrap().len(), folded.rings().unwrap().len());
+
+        // disjoint chains are concatenated
+        let s0 = SimplicalChain::from_polygon(&polygon_cube(Point::new([0.0, 0.0]), 1.0));
+        let s1 = SimplicalChain::from_polygon(&polygon_cube(Point::new([5.0, 0.0]), 1.0));
+        let sx = SimplicalChain::union_all([s0.clone(), s1.clone(), SimplicalChain::default()]);
+        assert_eq!(sx.simplices.len(), 8);
+        assert_eq!(sx.signed_area(), 8.0);
+
+        assert!(SimplicalChain::<f64>::union_all([]).simplices.is_empty());
+    }
+
+    #[test]
+    fn intersections_touching_end() {
+        // the end point of s1 touches the inside of s0, without crossing it
+        let s0 = Simplex {
+            src: Point::new([0.0, 0.0]),
+            dst: Point::new([2.0, 0.0]),
+        };
+        let s1 = Simplex {
+            src: Point::new([1.0, 0.0]),
+            dst: Point::new([1.0, 1.0]),
+        };
+        for (a, b) in [(&s0, &s1), (&s1, &s0)] {
+            let mut points = Vec::new();
+            a.intersections(b, |p| points.push(p));
+            assert_eq!(points, vec![Point::new([1.0, 0.0])]);
+        }
+
+        // shared end points are not intersections
+        let s2 = Simplex {
+            src: Point::new([2.0, 0.0]),
+            dst: Point::new([2.0, 1.0]),
+        };
+        let mut points = Vec::new();
+        s0.intersections(&s2, |p| points.push(p));
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn cancel_opposite() {
+        let [a, b, c] = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]].map(Point::new);
+        let simplex = |src: Point<f64>, dst: Point<f64>| Simplex { src, dst };
+        let sx = SimplicalChain {
+            simplices: vec![
+                simplex(a, b),
+                simplex(b, c),
+                simplex(b, a),
+                simplex(a, b),
+                simplex(c, a),
+            ],
+        };
+        assert_eq!(
+            sx.cancel_opposite().simplices,
+            vec![simplex(b, c), simplex(a, b), simplex(c, a)]
+        );
+    }
+
+    #[test]
+    fn union_touching_vertex() {
+        // a vertex of p1 touches the right side of p0, which is split there
+        let p0 = polygon_cube(Point::new([1.0, 1.0]), 1.0);
+        let p1 = Polygon::new(
+            [[2.0, 1.0], [3.0, 0.0], [3.0, 3.0], [1.5, 2.5]]
+                .map(Point::new)
+                .to_vec(),
+        )
+        .unwrap();
+        let s0 = SimplicalChain::from_polygon(&p0);
+        let s1 = SimplicalChain::from_polygon(&p1);
+
+        let sx = s0.union(&s1);
+        assert_eq!(sx.rings().unwrap().len(), 1);
+        assert!((sx.signed_area() - (4.0 + 2.75 - 1.0 / 6.0)).abs() < 1e-9);
+    }
+
     #[ignore]
     #[test]
     fn test_crossing() {
@@ -0,0 +1,208 @@
+use crate::aabb::AABB;
+use crate::boolean::{Simplex, SimplicalChain};
+use crate::delaunay::*;
+use rgeometry::{data::*, PolygonScalar};
+
+/// Squared distance from `p` to the segment `a`-`b`.
+pub fn segment_dist_sq<T: PolygonScalar>(p: &Point<T>, a: &Point<T>, b: &Point<T>) -> T {
+    let zero = T::from_constant(0);
+
+    let abx = b.array[0].clone() - a.array[0].clone();
+    let aby = b.array[1].clone() - a.array[1].clone();
+    let apx = p.array[0].clone() - a.array[0].clone();
+    let apy = p.array[1].clone() - a.array[1].clone();
+
+    let len_sq = abx.clone() * abx.clone() + aby.clone() * aby.clone();
+    let dot = apx.clone() * abx.clone() + apy.clone() * aby.clone();
+    if len_sq == zero || dot <= zero {
+        return dist_sq(p, a);
+    }
+    if dot >= len_sq {
+        return dist_sq(p, b);
+    }
+
+    let cross = apx * aby - apy * abx;
+    cross.clone() * cross / len_sq
+}
+
+// squared distance from `p` to the box, zero inside
+fn aabb_dist_sq<T: PolygonScalar>(aabb: &AABB<T>, p: &Point<T>) -> T {
+    let mut sum = T::from_constant(0);
+    for i in 0..2 {
+        let v = &p.array[i];
+        let d = if *v < aabb.min.array[i] {
+            aabb.min.array[i].clone() - v.clone()
+        } else if *v > aabb.max.array[i] {
+            v.clone() - aabb.max.array[i].clone()
+        } else {
+            continue;
+        };
+        sum += d.clone() * d;
+    }
+    sum
+}
+
+// bounding box hierarchy of simplices, split at the median along the longer side
+enum SimplexTree<T> {
+    Leaf(AABB<T>, usize),
+    Node(AABB<T>, Box<[SimplexTree<T>; 2]>),
+}
+
+impl<T: PolygonScalar> SimplexTree<T> {
+    fn new(simplices: &[Simplex<T>]) -> Option<Self> {
+        let mut indices = (0..simplices.len()).collect::<Vec<_>>();
+        if indices.is_empty() {
+            return None;
+        }
+        Some(Self::build(simplices, &mut indices))
+    }
+
+    fn build(simplices: &[Simplex<T>], indices: &mut [usize]) -> Self {
+        let mut aabb = AABB::new(&simplices[indices[0]].src);
+        for idx in indices.iter() {
+            aabb.extend(&simplices[*idx].src);
+            aabb.extend(&simplices[*idx].dst);
+        }
+        if let [idx] = indices {
+            return SimplexTree::Leaf(aabb, *idx);
+        }
+
+        let [x0, y0] = aabb.min.array.clone();
+        let [x1, y1] = aabb.max.array.clone();
+        let axis = if x1 - x0 >= y1 - y0 { 0 } else { 1 };
+        let mid = indices.len() / 2;
+        indices.select_nth_unstable_by(mid, |a, b| {
+            let a = &simplices[*a];
+            let b = &simplices[*b];
+            let a = a.src.array[axis].clone() + a.dst.array[axis].clone();
+            let b = b.src.array[axis].clone() + b.dst.array[axis].clone();
+            a.partial_cmp(&b).unwrap()
+        });
+        let (lo, hi) = indices.split_at_mut(mid);
+        let children = [Self::build(simplices, lo), Self::build(simplices, hi)];
+        SimplexTree::Node(aabb, Box::new(children))
+    }
+
+    fn aabb(&self) -> &AABB<T> {
+        match self {
+            SimplexTree::Leaf(aabb, _) | SimplexTree::Node(aabb, _) => aabb,
+        }
+    }
+
+    // lower `best` to the squared distance from `p` to the nearest simplex, if it is nearer
+    fn nearest(&self, simplices: &[Simplex<T>], p: &Point<T>, best: &mut T) {
+        match self {
+            SimplexTree::Leaf(_, idx) => {
+                let s = &simplices[*idx];
+                let d = segment_dist_sq(p, &s.src, &s.dst);
+                if d < *best {
+                    *best = d;
+                }
+            }
+            SimplexTree::Node(_, children) => {
+                let mut near = children
+                    .iter()
+                    .map(|c| (aabb_dist_sq(c.aabb(), p), c))
+                    .collect::<Vec<_>>();
+                near.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                for (d, c) in near {
+                    if d < *best {
+                        c.nearest(simplices, p, best);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    /// Largest circle centered in `free`, which does not cross the boundary of `free`. Candidate
+    /// centers are circumcenters of non-super triangles, and their radii are clamped by the
+    /// distance to the boundary, found with a bounding box hierarchy of the simplices. The circle
+    /// does not contain vertices of a Delaunay network, but circumcircles of a constrained network
+    /// from `build_net` may contain vertices behind constrained edges. Returns the center and the
+    /// squared radius.
+    pub fn largest_empty_circle(&self, free: &SimplicalChain<T>) -> Option<(Point<T>, T)> {
+        let tree = SimplexTree::new(&free.simplices)?;
+
+        let mut best: Option<(Point<T>, T)> = None;
+        for (idx, t) in self.triangles() {
+            if t.is_super() {
+                continue;
+            }
+
+            let (center, mut r_sq) = self.circumcircle(idx);
+            tree.nearest(&free.simplices, &center, &mut r_sq);
+            if let Some((_, best_r_sq)) = &best {
+                if r_sq <= *best_r_sq {
+                    continue;
+                }
+            }
+            if free.characteristic(&center) <= 0.5 {
+                continue;
+            }
+            best = Some((center, r_sq));
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn segment_dist() {
+        let a = Point::new([0.0, 0.0]);
+        let b = Point::new([4.0, 0.0]);
+
+        assert_eq!(segment_dist_sq(&Point::new([2.0, 3.0]), &a, &b), 9.0);
+        assert_eq!(segment_dist_sq(&Point::new([-3.0, 4.0]), &a, &b), 25.0);
+        assert_eq!(segment_dist_sq(&Point::new([7.0, -4.0]), &a, &b), 25.0);
+        assert_eq!(segment_dist_sq(&Point::new([1.0, 1.0]), &a, &a), 2.0);
+    }
+
+    #[test]
+    fn simplex_tree_nearest() {
+        use rand::prelude::*;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let rects = crate::gen_rects(&mut rng, 30.0, 20);
+        let free = SimplicalChain {
+            simplices: rects
+                .iter()
+                .flat_map(|r| SimplicalChain::from_polygon(&r.polygon(1)).simplices)
+                .collect(),
+        };
+        let tree = SimplexTree::new(&free.simplices).unwrap();
+
+        for _ in 0..100 {
+            let p = Point::new([rng.gen_range(-40.0..40.0), rng.gen_range(-40.0..40.0)]);
+            let expected = free
+                .simplices
+                .iter()
+                .map(|s| segment_dist_sq(&p, &s.src, &s.dst))
+                .fold(f64::MAX, f64::min);
+            let mut best = f64::MAX;
+            tree.nearest(&free.simplices, &p, &mut best);
+            assert_eq!(best, expected);
+        }
+    }
+
+    #[test]
+    fn largest_empty_circle_square() {
+        let square = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let net = test_net(&square.map(Point::new));
+
+        let polygon = Polygon::new(square.iter().map(|p| Point::new(*p)).collect()).unwrap();
+        let free = SimplicalChain::from_polygon(&polygon);
+
+        let (center, r_sq) = net.largest_empty_circle(&free).unwrap();
+        assert_eq!(center, Point::new([5.0, 5.0]));
+        assert_eq!(r_sq, 25.0);
+
+        assert!(net
+            .largest_empty_circle(&SimplicalChain::default())
+            .is_none());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::boolean::SimplicalChain;
+use crate::delaunay::*;
+use crate::proximity::DisjointSet;
+use crate::try_build_net;
+use rgeometry::{data::*, Orientation};
+
+fn is_reflex(net: &TriangularNetwork<f64>, v_prev: VertIdx, v: VertIdx, v_next: VertIdx) -> bool {
+    let o = Point::orient_along_direction(
+        net.vert(v_prev),
+        Direction::Through(net.vert(v)),
+        net.vert(v_next),
+    );
+    o == Orientation::ClockWise
+}
+
+// merge polygons sharing the side `v0 -> v1` of `poly0`, if the result is convex
+fn merge_convex(
+    net: &TriangularNetwork<f64>,
+    poly0: &[VertIdx],
+    poly1: &[VertIdx],
+    v0: VertIdx,
+    v1: VertIdx,
+) -> Option<Vec<VertIdx>> {
+    let len0 = poly0.len();
+    let len1 = poly1.len();
+    let i0 = poly0.iter().position(|v| *v == v0)?;
+    let i1 = poly1.iter().position(|v| *v == v1)?;
+    if poly0[(i0 + 1) % len0] != v1 || poly1[(i1 + 1) % len1] != v0 {
+        return None;
+    }
+
+    // at `v0`, coming from `poly0` and leaving to `poly1`, and the opposite at `v1`
+    if is_reflex(
+        net,
+        poly0[(i0 + len0 - 1) % len0],
+        v0,
+        poly1[(i1 + 2) % len1],
+    ) || is_reflex(
+        net,
+        poly1[(i1 + len1 - 1) % len1],
+        v1,
+        poly0[(i0 + 2) % len0],
+    ) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(len0 + len1 - 2);
+    for i in 0..len0 {
+        out.push(poly0[(i0 + 1 + i) % len0]);
+    }
+    for i in 1..(len1 - 1) {
+        out.push(poly1[(i1 + 1 + i) % len1]);
+    }
+    Some(out)
+}
+
+/// Decompose the interior of `sx` into convex polygons. The interior is triangulated with
+/// `build_net`, and then diagonals are removed greedily while the polygons on both sides stay
+/// convex (Hertel-Mehlhorn). The result has at most four times as many polygons as an optimal
+/// decomposition. Fails if the triangulation fails.
+pub fn convex_decomposition(sx: &SimplicalChain<f64>) -> anyhow::Result<Vec<Polygon<f64>>> {
+    let view = sx
+        .simplices
+        .iter()
+        .flat_map(|s| s.src.array)
+        .fold(1.0f64, |view, v| view.max(v.abs()));
+
+    let (net, c) = try_build_net(view, sx, true)?;
+    let inside = net.constrained_interior(&c);
+
+    let mut polys = vec![Vec::new(); net.tri_len()];
+    for (idx, t) in net.triangles() {
+        polys[idx.0] = t.vertices.to_vec();
+    }
+    let mut set = DisjointSet::new(net.tri_len());
+
+    for (idx, t) in net.triangles() {
+        let idx = idx.0;
+        if !inside[idx] {
+            continue;
+        }
+        for i in 0..3 {
+            let sub = SubIdx(i);
+            let n = match t.neighbors[i] {
+                Some(n) => n,
+                None => continue,
+            };
+            let v0 = t.vert(sub.cw());
+            let v1 = t.vert(sub);
+            if !inside[n.0]
+                || c.binary_search(&(v0, v1)).is_ok()
+                || c.binary_search(&(v1, v0)).is_ok()
+            {
+                continue;
+            }
+
+            let root0 = set.find(idx);
+            let root1 = set.find(n.0);
+            if root0 == root1 {
+                continue;
+            }
+            if let Some(merged) = merge_convex(&net, &polys[root0], &polys[root1], v0, v1) {
+                set.union(root0, root1);
+                polys[root0] = merged;
+                polys[root1].clear();
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for idx in 0..net.tri_len() {
+        if !inside[idx] || set.find(idx) != idx {
+            continue;
+        }
+        let points = polys[idx].iter().map(|v| *net.vert(*v)).collect();
+        out.push(Polygon::new_unchecked(points));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chain(points: &[[f64; 2]]) -> SimplicalChain<f64> {
+        let p = Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap();
+        SimplicalChain::from_polygon(&p)
+    }
+
+    fn area(p: &Polygon<f64>) -> f64 {
+        SimplicalChain::from_polygon(p).signed_area()
+    }
+
+    fn is_convex(p: &Polygon<f64>) -> bool {
+        let points = p.iter().collect::<Vec<_>>();
+        let len = points.len();
+        (0..len).all(|i| {
+            let o = Point::orient_along_direction(
+                points[i],
+                Direction::Through(points[(i + 1) % len]),
+                points[(i + 2) % len],
+            );
+            o != Orientation::ClockWise
+        })
+    }
+
+    #[test]
+    fn convex_square() {
+        let sx = chain(&[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]]);
+        let polys = convex_decomposition(&sx).unwrap();
+        assert_eq!(polys.len(), 1);
+        assert_eq!(area(&polys[0]), 4.0);
+    }
+
+    #[test]
+    fn convex_l_shape() {
+        let sx = chain(&[
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ]);
+        let polys = convex_decomposition(&sx).unwrap();
+
+        // one reflex vertex, so at most three pieces
+        assert!((2..=3).contains(&polys.len()), "{:?}", polys);
+        for p in &polys {
+            assert!(is_convex(p), "{:?}", p);
+        }
+        assert_eq!(polys.iter().map(area).sum::<f64>(), 3.0);
+    }
+}
@@ -1,6 +1,7 @@
 // https://www.personal.psu.edu/cxc11/AERSP560/DELAUNEY/13_Two_algorithms_Delauney.pdf
 use crate::visibility::VisibilityResult;
 use rgeometry::{data::*, Orientation, PolygonScalar};
+use std::sync::{Arc, Mutex};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct TriIdx(pub usize);
@@ -48,9 +49,19 @@ pub struct Edge {
     sub: SubIdx,
 }
 impl Edge {
-    fn new(tri: TriIdx, sub: SubIdx) -> Self {
+    pub(crate) fn new(tri: TriIdx, sub: SubIdx) -> Self {
         Self { tri, sub }
     }
+
+    /// triangle which owns the edge
+    pub fn tri(&self) -> TriIdx {
+        self.tri
+    }
+
+    /// index of the edge in the triangle, see `TriangularNetwork::edge_from`
+    pub fn sub(&self) -> SubIdx {
+        self.sub
+    }
 }
 
 #[derive(Debug)]
@@ -115,10 +126,136 @@ where
     Point::new([x / l.clone(), y / l.clone()])
 }
 
+/// Squared distance between two points
+pub(crate) fn dist_sq<T: PolygonScalar>(p: &Point<T>, q: &Point<T>) -> T {
+    let dx = p.array[0].clone() - q.array[0].clone();
+    let dy = p.array[1].clone() - q.array[1].clone();
+    dx.clone() * dx + dy.clone() * dy
+}
+
+/// index of a missing neighbor, in `TriangularNetwork::tri_neighbors`
+const NO_TRI: u32 = u32::MAX;
+
+fn encode_tri(idx: Option<TriIdx>) -> u32 {
+    match idx {
+        Some(idx) => idx.0 as u32,
+        None => NO_TRI,
+    }
+}
+
+fn decode_tri(idx: u32) -> Option<TriIdx> {
+    if idx == NO_TRI {
+        None
+    } else {
+        Some(TriIdx(idx as usize))
+    }
+}
+
+/// vertices of a free triangle slot, see `TriangularNetwork::is_free`
+const FREE_TRI: [u32; 3] = [0; 3];
+
+/// Renumbering done by `TriangularNetwork::compact`, indexed by old indices. `None` if the
+/// triangle or the vertex was dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Remap {
+    pub tris: Vec<Option<TriIdx>>,
+    pub verts: Vec<Option<VertIdx>>,
+}
+
+impl Remap {
+    pub fn tri(&self, idx: TriIdx) -> Option<TriIdx> {
+        self.tris[idx.0]
+    }
+
+    pub fn vert(&self, idx: VertIdx) -> Option<VertIdx> {
+        self.verts[idx.0]
+    }
+}
+
+/// Callbacks for mutations of `TriangularNetwork`, see `TriangularNetwork::set_observer`. Each
+/// callback is called after the mutation, with the updated network.
+#[allow(unused_variables)]
+pub trait NetworkObserver<T> {
+    /// `vert` was inserted, splitting a triangle or the two triangles of an edge into `tris`.
+    /// Indices of the split triangles are reused.
+    fn on_split(&mut self, net: &TriangularNetwork<T>, vert: VertIdx, tris: &[TriIdx]) {}
+
+    /// The diagonal between two adjacent triangles was swapped, keeping their indices.
+    fn on_flip(&mut self, net: &TriangularNetwork<T>, t0: TriIdx, t1: TriIdx) {}
+
+    /// The redundant weighted vertex `vert` was removed, merging its three triangles into `tri`.
+    fn on_remove(&mut self, net: &TriangularNetwork<T>, vert: VertIdx, tri: TriIdx) {}
+
+    /// Triangles crossed by `cut` were retriangulated, reusing their indices.
+    fn on_cut(&mut self, net: &TriangularNetwork<T>, cut: &Cut) {}
+
+    /// The edge between `v_from` and `v_to` was constrained.
+    fn on_constrain(&mut self, net: &TriangularNetwork<T>, v_from: VertIdx, v_to: VertIdx) {}
+
+    /// Triangles and vertices were renumbered by `compact`.
+    fn on_compact(&mut self, net: &TriangularNetwork<T>, remap: &Remap) {}
+}
+
+/// Shared observer, so the caller can keep a handle to read it back
+impl<T, O: NetworkObserver<T>> NetworkObserver<T> for Arc<Mutex<O>> {
+    fn on_split(&mut self, net: &TriangularNetwork<T>, vert: VertIdx, tris: &[TriIdx]) {
+        self.lock().unwrap().on_split(net, vert, tris)
+    }
+
+    fn on_flip(&mut self, net: &TriangularNetwork<T>, t0: TriIdx, t1: TriIdx) {
+        self.lock().unwrap().on_flip(net, t0, t1)
+    }
+
+    fn on_remove(&mut self, net: &TriangularNetwork<T>, vert: VertIdx, tri: TriIdx) {
+        self.lock().unwrap().on_remove(net, vert, tri)
+    }
+
+    fn on_cut(&mut self, net: &TriangularNetwork<T>, cut: &Cut) {
+        self.lock().unwrap().on_cut(net, cut)
+    }
+
+    fn on_constrain(&mut self, net: &TriangularNetwork<T>, v_from: VertIdx, v_to: VertIdx) {
+        self.lock().unwrap().on_constrain(net, v_from, v_to)
+    }
+
+    fn on_compact(&mut self, net: &TriangularNetwork<T>, remap: &Remap) {
+        self.lock().unwrap().on_compact(net, remap)
+    }
+}
+
+// observer of a network. clones of the network are not observed, see `set_observer`
+struct Observer<T>(Option<Box<dyn NetworkObserver<T> + Send + Sync>>);
+
+impl<T> Clone for Observer<T> {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl<T> std::fmt::Debug for Observer<T> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.0 {
+            Some(_) => write!(fmt, "Some(..)"),
+            None => write!(fmt, "None"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TriangularNetwork<T> {
     pub vertices: Vec<Point<T>>,
-    pub triangles: Vec<Triangle>,
+    // triangles as struct-of-arrays, indexed by `TriIdx`. see `Triangle` for the layout
+    tri_vertices: Vec<[u32; 3]>,
+    tri_neighbors: Vec<[u32; 3]>,
+    // free triangle slots left by removed triangles, reused by `add_tri`. cuts reuse the slots of
+    // the triangles they cross, so they do not free slots
+    free_tris: Vec<u32>,
+    // the last triangle written, which is not free. point location starts from it
+    live_tri: u32,
+    // vertex weights for the regular triangulation, indexed by `VertIdx`. missing weights are
+    // zero, and the network is unweighted if empty
+    weights: Vec<T>,
+    observer: Observer<T>,
 }
 
 impl<T: PolygonScalar> TriangularNetwork<T> {
@@ -133,31 +270,187 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
 
         Self {
             vertices: vec![p0, p1, p2],
-            triangles: vec![Triangle {
-                vertices: [VertIdx(0), VertIdx(1), VertIdx(2)],
-                neighbors: [None, None, None],
-            }],
+            tri_vertices: vec![[0, 1, 2]],
+            tri_neighbors: vec![[NO_TRI; 3]],
+            free_tris: Vec::new(),
+            live_tri: 0,
+            weights: Vec::new(),
+            observer: Observer(None),
+        }
+    }
+
+    pub(crate) fn from_triangles(vertices: Vec<Point<T>>, triangles: &[Triangle]) -> Self {
+        let mut net = Self {
+            vertices,
+            tri_vertices: Vec::with_capacity(triangles.len()),
+            tri_neighbors: Vec::with_capacity(triangles.len()),
+            free_tris: Vec::new(),
+            live_tri: 0,
+            weights: Vec::new(),
+            observer: Observer(None),
+        };
+        for t in triangles {
+            let idx = net.add_tri();
+            net.set_tri(idx, *t);
+        }
+        net
+    }
+
+    /// Observe mutations of the network, and return the previous observer. To read the
+    /// observer back while it is set, pass an `Arc<Mutex<_>>` and keep a clone of it. Clones of
+    /// the network start without an observer, so mutations of a clone are not reported.
+    pub fn set_observer(
+        &mut self,
+        observer: Option<Box<dyn NetworkObserver<T> + Send + Sync>>,
+    ) -> Option<Box<dyn NetworkObserver<T> + Send + Sync>> {
+        std::mem::replace(&mut self.observer, Observer(observer)).0
+    }
+
+    // the observer is taken out of the network during the callback
+    fn notify(&mut self, f: impl FnOnce(&mut dyn NetworkObserver<T>, &Self)) {
+        if let Some(mut observer) = self.observer.0.take() {
+            f(&mut *observer, self);
+            self.observer.0 = Some(observer);
+        }
+    }
+
+    /// Triangle at `idx`, decoded from the compact storage. Triangles used to be a public
+    /// `Vec<Triangle>` field, and this returned a reference to it. Callers now get a copy, and
+    /// iterate with `triangles` and `tri_len` instead of the field.
+    pub fn tri(&self, idx: TriIdx) -> Triangle {
+        Triangle {
+            vertices: self.tri_vertices[idx.0].map(|v| VertIdx(v as usize)),
+            neighbors: self.tri_neighbors[idx.0].map(decode_tri),
+        }
+    }
+
+    fn set_tri(&mut self, idx: TriIdx, t: Triangle) {
+        self.tri_vertices[idx.0] = t.vertices.map(|v| v.0 as u32);
+        self.tri_neighbors[idx.0] = t.neighbors.map(encode_tri);
+        self.live_tri = idx.0 as u32;
+    }
+
+    fn set_neighbor(&mut self, idx: TriIdx, sub: SubIdx, neighbor: Option<TriIdx>) {
+        self.tri_neighbors[idx.0][sub.0] = encode_tri(neighbor);
+    }
+
+    // replace the neighbor `idx_from` of `idx` with `idx_to`. false if `idx_from` is not a
+    // neighbor of `idx`
+    fn update_neighbor(&mut self, idx: TriIdx, idx_from: TriIdx, idx_to: TriIdx) -> bool {
+        let neighbors = &mut self.tri_neighbors[idx.0];
+        for n in neighbors.iter_mut() {
+            if *n == idx_from.0 as u32 {
+                *n = idx_to.0 as u32;
+                return true;
+            }
         }
+        false
+    }
+
+    /// Number of triangle slots, including super triangles and free slots.
+    pub fn tri_len(&self) -> usize {
+        self.tri_vertices.len()
     }
 
-    pub fn tri(&self, idx: TriIdx) -> &Triangle {
-        &self.triangles[idx.0]
+    /// Whether the triangle slot is free. Free slots are not reachable from other triangles, and
+    /// are skipped by `triangles`.
+    pub fn is_free(&self, idx: TriIdx) -> bool {
+        self.tri_vertices[idx.0] == FREE_TRI
     }
 
-    fn tri_mut(&mut self, idx: TriIdx) -> &mut Triangle {
-        &mut self.triangles[idx.0]
+    /// Iterate triangles in the order of `TriIdx`, skipping free slots.
+    pub fn triangles(&self) -> impl Iterator<Item = (TriIdx, Triangle)> + '_ {
+        (0..self.tri_len())
+            .map(TriIdx)
+            .filter(|idx| !self.is_free(*idx))
+            .map(|idx| (idx, self.tri(idx)))
     }
 
     fn add_tri(&mut self) -> TriIdx {
-        let v = VertIdx(0);
-        let idx = self.triangles.len();
-        self.triangles.push(Triangle {
-            vertices: [v, v, v],
-            neighbors: [None, None, None],
-        });
+        if let Some(idx) = self.free_tris.pop() {
+            return TriIdx(idx as usize);
+        }
+
+        let idx = self.tri_len();
+        assert!(idx < NO_TRI as usize);
+        self.tri_vertices.push(FREE_TRI);
+        self.tri_neighbors.push([NO_TRI; 3]);
         TriIdx(idx)
     }
 
+    // the triangle should not be referenced by its neighbors, nor be `live_tri`
+    fn free_tri(&mut self, idx: TriIdx) {
+        assert!(idx.0 as u32 != self.live_tri);
+        self.tri_vertices[idx.0] = FREE_TRI;
+        self.tri_neighbors[idx.0] = [NO_TRI; 3];
+        self.free_tris.push(idx.0 as u32);
+    }
+
+    /// Drop free triangle slots and vertices which are not used by any triangle, and renumber
+    /// the rest, keeping their order. Super vertices are always kept.
+    pub fn compact(&mut self) -> Remap {
+        let mut tris = vec![None; self.tri_len()];
+        let mut tri_count = 0;
+        for (idx, remap) in tris.iter_mut().enumerate() {
+            if !self.is_free(TriIdx(idx)) {
+                *remap = Some(TriIdx(tri_count));
+                tri_count += 1;
+            }
+        }
+
+        let mut used = vec![false; self.vertices.len()];
+        used[..3].fill(true);
+        for (idx, vertices) in self.tri_vertices.iter().enumerate() {
+            if tris[idx].is_some() {
+                for v in vertices {
+                    used[*v as usize] = true;
+                }
+            }
+        }
+        let mut verts = vec![None; self.vertices.len()];
+        let mut vert_count = 0;
+        for (idx, remap) in verts.iter_mut().enumerate() {
+            if used[idx] {
+                *remap = Some(VertIdx(vert_count));
+                vert_count += 1;
+            }
+        }
+
+        let vertices = std::mem::take(&mut self.vertices);
+        self.vertices = vertices
+            .into_iter()
+            .zip(&used)
+            .filter_map(|(p, used)| used.then_some(p))
+            .collect();
+        let weights = std::mem::take(&mut self.weights);
+        self.weights = weights
+            .into_iter()
+            .zip(&used)
+            .filter_map(|(w, used)| used.then_some(w))
+            .collect();
+
+        let tri_vertices = std::mem::take(&mut self.tri_vertices);
+        let tri_neighbors = std::mem::take(&mut self.tri_neighbors);
+        self.tri_vertices = Vec::with_capacity(tri_count);
+        self.tri_neighbors = Vec::with_capacity(tri_count);
+        for (idx, (vertices, neighbors)) in tri_vertices.into_iter().zip(tri_neighbors).enumerate()
+        {
+            if tris[idx].is_none() {
+                continue;
+            }
+            self.tri_vertices
+                .push(vertices.map(|v| verts[v as usize].unwrap().0 as u32));
+            self.tri_neighbors
+                .push(neighbors.map(|n| encode_tri(decode_tri(n).and_then(|n| tris[n.0]))));
+        }
+        self.free_tris.clear();
+        self.live_tri = tris[self.live_tri as usize].unwrap().0 as u32;
+
+        let remap = Remap { tris, verts };
+        self.notify(|o, net| o.on_compact(net, &remap));
+        remap
+    }
+
     pub fn find_vert(&self, p: &Point<T>) -> Option<VertIdx> {
         self.vertices.iter().position(|v| v == p).map(VertIdx)
     }
@@ -168,24 +461,73 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
 
     fn add_vert(&mut self, p: Point<T>) -> VertIdx {
         let idx = self.vertices.len();
+        assert!(idx < u32::MAX as usize);
         self.vertices.push(p);
         VertIdx(idx)
     }
 
+    fn add_weighted_vert(&mut self, p: Point<T>, weight: Option<T>) -> VertIdx {
+        let idx = self.add_vert(p);
+        if let Some(weight) = weight {
+            self.weights.resize(idx.0 + 1, T::from_constant(0));
+            self.weights[idx.0] = weight;
+        }
+        idx
+    }
+
+    /// Weight of the vertex, zero for vertices inserted without weights.
+    pub fn weight(&self, idx: VertIdx) -> T {
+        match self.weights.get(idx.0) {
+            Some(w) => w.clone(),
+            None => T::from_constant(0),
+        }
+    }
+
     pub fn tri_vert(&self, tri_idx: TriIdx, idx: SubIdx) -> &Point<T> {
-        self.vert(self.tri(tri_idx).vertices[idx.0])
+        self.vert(self.tri_vert_idx(tri_idx, idx))
+    }
+
+    // a single vertex or neighbor of a triangle, without decoding the whole triangle. these are
+    // on the hot paths of point location and cuts
+    fn tri_vert_idx(&self, tri_idx: TriIdx, idx: SubIdx) -> VertIdx {
+        VertIdx(self.tri_vertices[tri_idx.0][idx.0] as usize)
+    }
+
+    fn tri_neighbor(&self, tri_idx: TriIdx, idx: SubIdx) -> Option<TriIdx> {
+        decode_tri(self.tri_neighbors[tri_idx.0][idx.0])
+    }
+
+    /// List of edges between non-super vertices. Each edge is listed once, as `(v0, v1)` with
+    /// `v0 < v1`, in sorted order.
+    pub fn edges(&self) -> Vec<(VertIdx, VertIdx)> {
+        let mut edges = Vec::with_capacity(self.tri_len() * 3 / 2);
+        for (_, t) in self.triangles() {
+            for i in 0..3 {
+                let v_from = t.vert(SubIdx(i).cw());
+                let v_to = t.vert(SubIdx(i));
+                if v_from.is_super() || v_to.is_super() {
+                    continue;
+                }
+                edges.push((v_from.min(v_to), v_from.max(v_to)));
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        edges
     }
 
     pub fn edge_duel(&self, edge: &Edge) -> Option<Edge> {
-        let t = self.tri(edge.tri);
-        let idx_neighbor = t.neighbor(edge.sub)?;
-        let t_neighbor = self.tri(idx_neighbor);
-        let sub_neighbor = match t_neighbor.neighbor_idx(edge.tri) {
-            Some(idx) => idx,
+        let idx_neighbor = self.tri_neighbor(edge.tri, edge.sub)?;
+        let neighbors = &self.tri_neighbors[idx_neighbor.0];
+        let sub_neighbor = match neighbors.iter().position(|n| *n == edge.tri.0 as u32) {
+            Some(idx) => SubIdx(idx),
             None => {
                 panic!(
                     "invariant: t1={:?}={:?}, t2={:?}={:?}",
-                    edge.tri, t, idx_neighbor, t_neighbor
+                    edge.tri,
+                    self.tri(edge.tri),
+                    idx_neighbor,
+                    self.tri(idx_neighbor)
                 );
             }
         };
@@ -193,13 +535,11 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
     }
 
     pub fn edge_to(&self, edge: &Edge) -> VertIdx {
-        let t = self.tri(edge.tri);
-        t.vert(edge.sub)
+        self.tri_vert_idx(edge.tri, edge.sub)
     }
 
     pub fn edge_from(&self, edge: &Edge) -> VertIdx {
-        let t = self.tri(edge.tri);
-        t.vert(edge.sub.cw())
+        self.tri_vert_idx(edge.tri, edge.sub.cw())
     }
 
     fn find_vert_dest(&self, v_from: VertIdx, v_to: VertIdx) -> Option<CutIter> {
@@ -423,7 +763,7 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                     dirty.push(idx_p.unwrap());
                     return None;
                 } else {
-                    *self.tri_mut(outer.tri).neighbor_mut(outer.sub) = idx_p.map(|e| e.tri);
+                    self.set_neighbor(outer.tri, outer.sub, idx_p.map(|e| e.tri));
                     return Some(outer.tri);
                 }
             } else {
@@ -469,10 +809,13 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
             out,
         );
 
-        *self.tri_mut(idx_self) = Triangle {
-            vertices: [v_start, v_mid, v_end],
-            neighbors: [idx_p.map(|e| e.tri), idx_t0, idx_t1],
-        };
+        self.set_tri(
+            idx_self,
+            Triangle {
+                vertices: [v_start, v_mid, v_end],
+                neighbors: [idx_p.map(|e| e.tri), idx_t0, idx_t1],
+            },
+        );
 
         out.push((v_start, v_mid));
         out.push((v_mid, v_end));
@@ -556,8 +899,8 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
             let t_ccw = out_triangles[i];
             let t_cw = out_triangles[out_triangles.len() - 1 - i];
 
-            *self.tri_mut(t_ccw).neighbor_mut(SubIdx(0)) = Some(t_cw);
-            *self.tri_mut(t_cw).neighbor_mut(SubIdx(0)) = Some(t_ccw);
+            self.set_neighbor(t_ccw, SubIdx(0), Some(t_cw));
+            self.set_neighbor(t_cw, SubIdx(0), Some(t_ccw));
         }
 
         for i in 0..dirty.len() {
@@ -567,14 +910,17 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
 
                 if self.edge_from(e0) == self.edge_to(e1) && self.edge_to(e0) == self.edge_from(e1)
                 {
-                    *self.tri_mut(e0.tri).neighbor_mut(e0.sub) = Some(e1.tri);
-                    *self.tri_mut(e1.tri).neighbor_mut(e1.sub) = Some(e0.tri);
+                    self.set_neighbor(e0.tri, e0.sub, Some(e1.tri));
+                    self.set_neighbor(e1.tri, e1.sub, Some(e0.tri));
                 }
             }
         }
 
+        // both sides of the cut are retriangulated with as many triangles as were crossed, so all
+        // slots are reused
         assert!(indices.is_empty());
         self.check_invariant("post-cut_resolve")?;
+        self.notify(|o, net| o.on_cut(net, res));
 
         Ok(out)
     }
@@ -583,6 +929,7 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
     pub fn constrain_edge(&mut self, v_from: VertIdx, v_to: VertIdx) -> Result<()> {
         let cut = self.cut(v_from, v_to);
         self.cut_apply_inner(&cut)?;
+        self.notify(|o, net| o.on_constrain(net, v_from, v_to));
         Ok(())
     }
 
@@ -649,7 +996,7 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
 
     #[cfg(debug_assertions)]
     fn check_invariant(&self, msg: &str) -> Result<()> {
-        for idx in 0..self.triangles.len() {
+        for idx in 0..self.tri_len() {
             self.check_invariant_tri(TriIdx(idx), msg)?;
         }
         Ok(())
@@ -669,6 +1016,87 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
         )
     }
 
+    /// Swap the diagonal of the quadrilateral formed by `idx0` and its neighbor across `t0_t1_idx`.
+    /// Both triangles keep their indices, and the new diagonal is the first side of `idx0`.
+    /// Returns the neighbor. The quadrilateral should be convex.
+    pub(crate) fn flip(&mut self, idx0: TriIdx, t0_t1_idx: SubIdx) -> Result<TriIdx> {
+        let t0 = self.tri(idx0);
+        let idx1 = match t0.neighbor(t0_t1_idx) {
+            Some(idx) => idx,
+            None => anyhow::bail!("no tri to flip: {}", self.debug_tri(idx0)),
+        };
+        let t1 = self.tri(idx1);
+        let t1_t0_idx = match t1.neighbor_idx(idx0) {
+            Some(idx) => idx,
+            None => {
+                anyhow::bail!(
+                    "invalid tri pair: \n{}\n{}",
+                    self.debug_tri(idx0),
+                    self.debug_tri(idx1),
+                );
+            }
+        };
+
+        let v0 = t0.vert(t0_t1_idx);
+        let v1 = t0.vert(t0_t1_idx.ccw());
+        let v2 = t0.vert(t0_t1_idx.cw());
+        let v3 = t1.vert(t1_t0_idx.ccw());
+
+        let n0 = t1.neighbor(t1_t0_idx.cw());
+        let n1 = t0.neighbor(t0_t1_idx.ccw());
+        let n2 = t0.neighbor(t0_t1_idx.cw());
+        let n3 = t1.neighbor(t1_t0_idx.ccw());
+
+        self.set_tri(
+            idx0,
+            Triangle {
+                vertices: [v1, v2, v3],
+                neighbors: [Some(idx1), n2, n3],
+            },
+        );
+
+        self.set_tri(
+            idx1,
+            Triangle {
+                vertices: [v1, v3, v0],
+                neighbors: [n1, Some(idx0), n0],
+            },
+        );
+
+        // n0, n2 stays same, n1, n3 changes neighbor
+        if let Some(idx) = n1 {
+            if !self.update_neighbor(idx, idx0, idx1) {
+                anyhow::bail!(
+                    "invalid tri pair: \nt0={}\nt1={}\nn1={}",
+                    self.debug_tri(idx0),
+                    self.debug_tri(idx1),
+                    self.debug_tri(idx)
+                );
+            }
+        }
+        if let Some(idx) = n3 {
+            if !self.update_neighbor(idx, idx1, idx0) {
+                anyhow::bail!(
+                    "invalid tri pair: \nt0={}\nt1={}\nn3={}",
+                    self.debug_tri(idx0),
+                    self.debug_tri(idx1),
+                    self.debug_tri(idx)
+                );
+            }
+        }
+
+        self.check_invariant_tri(idx0, "pre-swap idx0")?;
+        self.check_invariant_tri(idx1, "pre-swap idx1")?;
+
+        self.check_invariant_tri_opt(n0, "pre-swap n0")?;
+        self.check_invariant_tri_opt(n1, "pre-swap n1")?;
+        self.check_invariant_tri_opt(n2, "pre-swap n2")?;
+        self.check_invariant_tri_opt(n3, "pre-swap n3")?;
+        self.notify(|o, net| o.on_flip(net, idx0, idx1));
+
+        Ok(idx1)
+    }
+
     fn maybe_swap(&mut self, idx0: TriIdx, reductions: &mut usize) -> Result<bool> {
         use Orientation::*;
 
@@ -718,72 +1146,168 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
             return Ok(false);
         }
 
-        if d0 == d1 || d2 == d3 {
+        if d0 == d1 {
             return Ok(false);
         }
+        if d2 == d3 {
+            return self.maybe_remove_reflex(idx0, t0_t1_idx, idx1, t1_t0_idx, reductions);
+        }
 
         let should_swap = if v0.is_super() || v2.is_super() {
             true
         } else if v1.is_super() || v3.is_super() {
             false
-        } else {
+        } else if self.weights.is_empty() {
             T::inside_circle(p0, p1, p2, p3)
+        } else {
+            self.power_test([v0, v1, v2], p3, &self.weight(v3))
         };
 
         if !should_swap {
             return Ok(false);
         }
 
-        // swap
+        self.flip(idx0, t0_t1_idx)?;
+
+        self.maybe_swap(idx0, reductions)?;
+        self.maybe_swap(idx1, reductions)?;
+
+        self.check_invariant("post-swap")?;
+
+        Ok(true)
+    }
+
+    // whether `p` with weight `w` is closer than orthogonal to the orthogonal circle of the
+    // counterclockwise `vertices`, by power distance. same as `inside_circle` if all weights are
+    // zero
+    fn power_test(&self, vertices: [VertIdx; 3], p: &Point<T>, w: &T) -> bool {
+        let [r0, r1, r2] = vertices.map(|v| {
+            let q = self.vert(v);
+            let dx = q.array[0].clone() - p.array[0].clone();
+            let dy = q.array[1].clone() - p.array[1].clone();
+            let dz = dx.clone() * dx.clone() + dy.clone() * dy.clone() - self.weight(v) + w.clone();
+            [dx, dy, dz]
+        });
+        let [ax, ay, az] = r0;
+        let [bx, by, bz] = r1;
+        let [cx, cy, cz] = r2;
+
+        let det = ax * (by.clone() * cz.clone() - bz.clone() * cy.clone())
+            - ay * (bx.clone() * cz - bz * cx.clone())
+            + az * (bx * cy - by * cx);
+        det > T::from_constant(0)
+    }
+
+    // whether `p` with weight `w` conflicts with the triangle, and should be a vertex of the
+    // regular triangulation
+    fn power_conflict(&self, idx: TriIdx, p: &Point<T>, w: &T) -> bool {
+        let t = self.tri(idx);
+        t.is_super() || self.power_test(t.vertices, p, w)
+    }
+
+    // 3-to-1 flip of the non-convex quadrilateral in `maybe_swap`. the reflex vertex is removed
+    // if it is redundant and has only three incident triangles. the triangles are merged into
+    // `idx0`, and the others are freed
+    fn maybe_remove_reflex(
+        &mut self,
+        idx0: TriIdx,
+        t0_t1_idx: SubIdx,
+        idx1: TriIdx,
+        t1_t0_idx: SubIdx,
+        reductions: &mut usize,
+    ) -> Result<bool> {
+        if self.weights.is_empty() {
+            return Ok(false);
+        }
+
+        let t0 = self.tri(idx0);
+        let t1 = self.tri(idx1);
+
+        let v0 = t0.vert(t0_t1_idx);
+        let v1 = t0.vert(t0_t1_idx.ccw());
+        let v2 = t0.vert(t0_t1_idx.cw());
+        let v3 = t1.vert(t1_t0_idx.ccw());
+        if [v0, v1, v2, v3].iter().any(|v| v.is_super()) {
+            return Ok(false);
+        }
+
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        let p2 = self.vert(v2);
+        let p3 = self.vert(v3);
+        if !self.power_test([v0, v1, v2], p3, &self.weight(v3)) {
+            return Ok(false);
+        }
+
+        // v3 -> v0, v0 -> v1, v1 -> v2, v2 -> v3
         let n0 = t1.neighbor(t1_t0_idx.cw());
         let n1 = t0.neighbor(t0_t1_idx.ccw());
         let n2 = t0.neighbor(t0_t1_idx.cw());
         let n3 = t1.neighbor(t1_t0_idx.ccw());
 
-        *self.tri_mut(idx0) = Triangle {
-            vertices: [v1, v2, v3],
-            neighbors: [Some(idx1), n2, n3],
-        };
+        let reflex_v2 =
+            Point::orient_along_direction(p1, Direction::Through(p2), p3) == Orientation::ClockWise;
+        let reflex_v0 =
+            Point::orient_along_direction(p3, Direction::Through(p0), p1) == Orientation::ClockWise;
 
-        *self.tri_mut(idx1) = Triangle {
-            vertices: [v1, v3, v0],
-            neighbors: [n1, Some(idx0), n0],
+        // the third triangle around the reflex vertex, and the side of it opposite to the vertex
+        let (idx2, v_from, v_to) = match (reflex_v2, reflex_v0) {
+            (true, false) if n2.is_some() && n2 == n3 => (n2.unwrap(), v1, v3),
+            (false, true) if n0.is_some() && n0 == n1 => (n0.unwrap(), v3, v1),
+            _ => return Ok(false),
+        };
+        let t2 = self.tri(idx2);
+        let n = (0..3)
+            .map(SubIdx)
+            .find(|sub| t2.vert(sub.cw()) == v_from && t2.vert(*sub) == v_to)
+            .and_then(|sub| t2.neighbor(sub));
+
+        //  v3 -- v1      v3 -- v1
+        //    \ v2 /   or   \ v0 /
+        //      v0            v2
+        let (t, n_moved) = if reflex_v2 {
+            let t = Triangle {
+                vertices: [v1, v3, v0],
+                neighbors: [n1, n, n0],
+            };
+            (t, n0)
+        } else {
+            let t = Triangle {
+                vertices: [v1, v2, v3],
+                neighbors: [n, n2, n3],
+            };
+            (t, n3)
         };
 
-        // n0, n2 stays same, n1, n3 changes neighbor
-        if let Some(idx) = n1 {
-            if !self.tri_mut(idx).update_neighbor(idx0, idx1) {
+        self.set_tri(idx0, t);
+        if let Some(idx) = n_moved {
+            if !self.update_neighbor(idx, idx1, idx0) {
                 anyhow::bail!(
-                    "invalid tri pair: \nt0={}\nt1={}\nn1={}",
-                    self.debug_tri(idx0),
-                    self.debug_tri(idx1),
-                    self.debug_tri(idx)
+                    "invalid neighbor: n_moved={}, from={:?}",
+                    self.debug_tri(idx),
+                    idx1
                 );
             }
         }
-        if let Some(idx) = n3 {
-            if !self.tri_mut(idx).update_neighbor(idx1, idx0) {
+        if let Some(idx) = n {
+            if !self.update_neighbor(idx, idx2, idx0) {
                 anyhow::bail!(
-                    "invalid tri pair: \nt0={}\nt1={}\nn3={}",
-                    self.debug_tri(idx0),
-                    self.debug_tri(idx1),
-                    self.debug_tri(idx)
+                    "invalid neighbor: n={}, from={:?}",
+                    self.debug_tri(idx),
+                    idx2
                 );
             }
         }
+        self.free_tri(idx1);
+        self.free_tri(idx2);
 
-        self.check_invariant_tri(idx0, "pre-swap idx0")?;
-        self.check_invariant_tri(idx1, "pre-swap idx1")?;
-
-        self.check_invariant_tri_opt(n0, "pre-swap n0")?;
-        self.check_invariant_tri_opt(n1, "pre-swap n1")?;
-        self.check_invariant_tri_opt(n2, "pre-swap n2")?;
-        self.check_invariant_tri_opt(n3, "pre-swap n3")?;
+        self.check_invariant_tri(idx0, "post-remove idx0")?;
+        self.check_invariant_tri_opt(n_moved, "post-remove n_moved")?;
+        self.check_invariant_tri_opt(n, "post-remove n")?;
+        let v_removed = if reflex_v2 { v2 } else { v0 };
+        self.notify(|o, net| o.on_remove(net, v_removed, idx0));
 
         self.maybe_swap(idx0, reductions)?;
-        self.maybe_swap(idx1, reductions)?;
-
-        self.check_invariant("post-swap")?;
 
         Ok(true)
     }
@@ -791,6 +1315,44 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
     /// Add a new point to the network. Returns existing `VertIdx` of the point is already in the
     /// network.
     pub fn insert(&mut self, p: &Point<T>, reductions: &mut usize) -> Result<VertIdx> {
+        self.insert_inner(p, None, reductions)
+    }
+
+    /// Add a new point with a weight, usually the squared radius of a circle centered at the
+    /// point, keeping the network a regular triangulation. Returns `None` if the point is
+    /// redundant, and is not a vertex of the regular triangulation. Existing vertices may become
+    /// redundant, and are removed from the triangles but kept in `vertices`, see `compact`. The
+    /// weight of an existing point is not changed.
+    pub fn insert_weighted(
+        &mut self,
+        p: &Point<T>,
+        weight: T,
+        reductions: &mut usize,
+    ) -> Result<Option<VertIdx>> {
+        use TriangularNetworkLocation::*;
+
+        let redundant = match self.locate_recursive(p) {
+            InTriangle(idx) => !self.power_conflict(idx, p, &weight),
+            OnEdge(e) => {
+                let n = self.tri(e.tri).neighbor(e.sub);
+                !self.power_conflict(e.tri, p, &weight)
+                    && !n.is_some_and(|n| self.power_conflict(n, p, &weight))
+            }
+            _ => false,
+        };
+        if redundant {
+            return Ok(None);
+        }
+
+        self.insert_inner(p, Some(weight), reductions).map(Some)
+    }
+
+    fn insert_inner(
+        &mut self,
+        p: &Point<T>,
+        weight: Option<T>,
+        reductions: &mut usize,
+    ) -> Result<VertIdx> {
         use TriangularNetworkLocation::*;
 
         if *reductions == 0 {
@@ -800,8 +1362,8 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
 
         match self.locate_recursive(p) {
             InTriangle(idx_t) => {
-                let idx_v = self.add_vert(p.clone());
-                let t = self.tri(idx_t).clone();
+                let idx_v = self.add_weighted_vert(p.clone(), weight);
+                let t = self.tri(idx_t);
 
                 let idx_t0 = idx_t;
                 let idx_t1 = self.add_tri();
@@ -810,29 +1372,51 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                 let [v0, v1, v2] = t.vertices;
                 let [n0, n1, n2] = t.neighbors;
 
-                *self.tri_mut(idx_t0) = Triangle {
-                    vertices: [idx_v, v0, v1],
-                    neighbors: [Some(idx_t1), Some(idx_t2), n1],
-                };
-                *self.tri_mut(idx_t1) = Triangle {
-                    vertices: [idx_v, v1, v2],
-                    neighbors: [Some(idx_t2), Some(idx_t0), n2],
-                };
-                *self.tri_mut(idx_t2) = Triangle {
-                    vertices: [idx_v, v2, v0],
-                    neighbors: [Some(idx_t0), Some(idx_t1), n0],
-                };
+                self.set_tri(
+                    idx_t0,
+                    Triangle {
+                        vertices: [idx_v, v0, v1],
+                        neighbors: [Some(idx_t1), Some(idx_t2), n1],
+                    },
+                );
+                self.set_tri(
+                    idx_t1,
+                    Triangle {
+                        vertices: [idx_v, v1, v2],
+                        neighbors: [Some(idx_t2), Some(idx_t0), n2],
+                    },
+                );
+                self.set_tri(
+                    idx_t2,
+                    Triangle {
+                        vertices: [idx_v, v2, v0],
+                        neighbors: [Some(idx_t0), Some(idx_t1), n0],
+                    },
+                );
 
                 if let Some(idx_neighbor) = n2 {
-                    self.tri_mut(idx_neighbor).update_neighbor(idx_t0, idx_t1);
+                    if !self.update_neighbor(idx_neighbor, idx_t0, idx_t1) {
+                        anyhow::bail!(
+                            "invalid neighbor: n2={}, from={:?}",
+                            self.debug_tri(idx_neighbor),
+                            idx_t0
+                        );
+                    }
                 }
                 if let Some(idx_neighbor) = n0 {
-                    self.tri_mut(idx_neighbor).update_neighbor(idx_t0, idx_t2);
+                    if !self.update_neighbor(idx_neighbor, idx_t0, idx_t2) {
+                        anyhow::bail!(
+                            "invalid neighbor: n0={}, from={:?}",
+                            self.debug_tri(idx_neighbor),
+                            idx_t0
+                        );
+                    }
                 }
 
                 self.check_invariant_tri(idx_t0, "InTriangle(t0)")?;
                 self.check_invariant_tri(idx_t1, "InTriangle(t1)")?;
                 self.check_invariant_tri(idx_t2, "InTriangle(t2)")?;
+                self.notify(|o, net| o.on_split(net, idx_v, &[idx_t0, idx_t1, idx_t2]));
 
                 self.maybe_swap(idx_t0, reductions)?;
                 self.maybe_swap(idx_t1, reductions)?;
@@ -848,7 +1432,7 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                 sub: idx_neighbor,
             }) => {
                 let idx_t0 = idx_t;
-                let t0 = self.tri(idx_t0).clone();
+                let t0 = self.tri(idx_t0);
 
                 let idx_t1 = t0.neighbor(idx_neighbor);
 
@@ -862,7 +1446,7 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                 let v0 = t0.vert(idx_neighbor);
                 let v1 = t0.vert(idx_neighbor.ccw());
                 let v2 = t0.vert(idx_neighbor.cw());
-                let idx_v = self.add_vert(p.clone());
+                let idx_v = self.add_weighted_vert(p.clone(), weight);
 
                 //       v2(v0)
                 //     t3     t2
@@ -870,42 +1454,66 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                 //     t1     t0
                 //       v0(v2)
 
-                *self.tri_mut(idx_t0) = Triangle {
-                    vertices: [idx_v, v0, v1],
-                    neighbors: [Some(idx_t2), idx_t1, t0.neighbor(idx_neighbor.ccw())],
-                };
+                self.set_tri(
+                    idx_t0,
+                    Triangle {
+                        vertices: [idx_v, v0, v1],
+                        neighbors: [Some(idx_t2), idx_t1, t0.neighbor(idx_neighbor.ccw())],
+                    },
+                );
 
                 let n = t0.neighbor(idx_neighbor.cw());
-                *self.tri_mut(idx_t2) = Triangle {
-                    vertices: [idx_v, v1, v2],
-                    neighbors: [idx_t3, Some(idx_t0), n],
-                };
+                self.set_tri(
+                    idx_t2,
+                    Triangle {
+                        vertices: [idx_v, v1, v2],
+                        neighbors: [idx_t3, Some(idx_t0), n],
+                    },
+                );
                 if let Some(n) = n {
-                    self.tri_mut(n).update_neighbor(idx_t0, idx_t2);
+                    if !self.update_neighbor(n, idx_t0, idx_t2) {
+                        anyhow::bail!(
+                            "invalid neighbor: n={}, from={:?}",
+                            self.debug_tri(n),
+                            idx_t0
+                        );
+                    }
                 }
 
                 if let Some(idx_t1) = idx_t1 {
                     let idx_t3 = idx_t3.unwrap();
 
-                    let t1 = self.tri(idx_t1).clone();
+                    let t1 = self.tri(idx_t1);
                     let idx_neighbor = t1.neighbor_idx(idx_t).unwrap();
 
                     let v0 = t1.vert(idx_neighbor);
                     let v1 = t1.vert(idx_neighbor.ccw());
                     let v2 = t1.vert(idx_neighbor.cw());
 
-                    *self.tri_mut(idx_t1) = Triangle {
-                        vertices: [idx_v, v1, v2],
-                        neighbors: [Some(idx_t0), Some(idx_t3), t1.neighbor(idx_neighbor.cw())],
-                    };
+                    self.set_tri(
+                        idx_t1,
+                        Triangle {
+                            vertices: [idx_v, v1, v2],
+                            neighbors: [Some(idx_t0), Some(idx_t3), t1.neighbor(idx_neighbor.cw())],
+                        },
+                    );
 
                     let n = t1.neighbor(idx_neighbor.ccw());
-                    *self.tri_mut(idx_t3) = Triangle {
-                        vertices: [idx_v, v0, v1],
-                        neighbors: [Some(idx_t1), Some(idx_t2), n],
-                    };
+                    self.set_tri(
+                        idx_t3,
+                        Triangle {
+                            vertices: [idx_v, v0, v1],
+                            neighbors: [Some(idx_t1), Some(idx_t2), n],
+                        },
+                    );
                     if let Some(n) = n {
-                        self.tri_mut(n).update_neighbor(idx_t1, idx_t3);
+                        if !self.update_neighbor(n, idx_t1, idx_t3) {
+                            anyhow::bail!(
+                                "invalid neighbor: n={}, from={:?}",
+                                self.debug_tri(n),
+                                idx_t1
+                            );
+                        }
                     }
                 }
 
@@ -917,6 +1525,9 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
                 if let Some(idx_t3) = idx_t3 {
                     self.check_invariant_tri(idx_t3, "Colinear(t3)")?;
                 }
+                let tris = [Some(idx_t0), idx_t1, Some(idx_t2), idx_t3];
+                let tris = tris.into_iter().flatten().collect::<Vec<_>>();
+                self.notify(|o, net| o.on_split(net, idx_v, &tris));
 
                 self.maybe_swap(idx_t0, reductions)?;
                 self.maybe_swap(idx_t2, reductions)?;
@@ -973,14 +1584,14 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
     }
 
     pub fn locate_recursive(&self, p: &Point<T>) -> TriangularNetworkLocation {
-        let mut start = TriIdx(0);
+        let mut start = TriIdx(self.live_tri as usize);
 
         loop {
             use TriangularNetworkLocation::*;
 
             start = match self.locate(start, p) {
                 Outside(e) => {
-                    match self.tri(e.tri).neighbor(e.sub) {
+                    match self.tri_neighbor(e.tri, e.sub) {
                         Some(idx) => idx,
                         None => {
                             eprintln!("{:?}, {:?}", e, self.tri(e.tri));
@@ -1079,6 +1690,118 @@ impl<T: PolygonScalar> TriangularNetwork<T> {
         pt_mean(&[p0, p1, p2])
     }
 
+    /// center and squared radius of the circumcircle
+    pub fn circumcircle(&self, tri: TriIdx) -> (Point<T>, T) {
+        let t = self.tri(tri);
+
+        let [v0, v1, v2] = t.vertices;
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        let p2 = self.vert(v2);
+
+        let bx = p1.array[0].clone() - p0.array[0].clone();
+        let by = p1.array[1].clone() - p0.array[1].clone();
+        let cx = p2.array[0].clone() - p0.array[0].clone();
+        let cy = p2.array[1].clone() - p0.array[1].clone();
+
+        let b_sq = bx.clone() * bx.clone() + by.clone() * by.clone();
+        let c_sq = cx.clone() * cx.clone() + cy.clone() * cy.clone();
+        let d = T::from_constant(2) * (bx.clone() * cy.clone() - by.clone() * cx.clone());
+
+        let ux = (cy * b_sq.clone() - by * c_sq.clone()) / d.clone();
+        let uy = (bx * c_sq - cx * b_sq) / d;
+
+        let r_sq = ux.clone() * ux.clone() + uy.clone() * uy.clone();
+        let center = Point::new([p0.array[0].clone() + ux, p0.array[1].clone() + uy]);
+        (center, r_sq)
+    }
+
+    /// Mark triangles inside of the constraints, indexed by `TriIdx`. `edges` are sorted
+    /// constraints with the interior on their left, as returned by `build_net`. Super triangles
+    /// are outside.
+    pub fn constrained_interior(&self, edges: &[(VertIdx, VertIdx)]) -> Vec<bool> {
+        let is_constrained = |v_from: VertIdx, v_to: VertIdx| {
+            edges.binary_search(&(v_from, v_to)).is_ok()
+                || edges.binary_search(&(v_to, v_from)).is_ok()
+        };
+
+        let mut inside = vec![None; self.tri_len()];
+        let mut queue = Vec::new();
+        for (idx, t) in self.triangles() {
+            let class = if t.is_super() {
+                Some(false)
+            } else {
+                (0..3).find_map(|i| {
+                    let v_from = t.vert(SubIdx(i).cw());
+                    let v_to = t.vert(SubIdx(i));
+                    if edges.binary_search(&(v_from, v_to)).is_ok() {
+                        Some(true)
+                    } else if edges.binary_search(&(v_to, v_from)).is_ok() {
+                        Some(false)
+                    } else {
+                        None
+                    }
+                })
+            };
+            if class.is_some() {
+                inside[idx.0] = class;
+                queue.push(idx);
+            }
+        }
+
+        // flood unclassified triangles through unconstrained edges
+        while let Some(idx) = queue.pop() {
+            let t = self.tri(idx);
+            for i in 0..3 {
+                let sub = SubIdx(i);
+                if is_constrained(t.vert(sub.cw()), t.vert(sub)) {
+                    continue;
+                }
+                if let Some(n) = t.neighbor(sub) {
+                    if inside[n.0].is_none() {
+                        inside[n.0] = inside[idx.0];
+                        queue.push(n);
+                    }
+                }
+            }
+        }
+
+        inside.into_iter().map(|c| c.unwrap_or(false)).collect()
+    }
+
+    /// Mark non-super triangles outside of the constraints, indexed by `TriIdx`.
+    pub fn free_space(&self, edges: &[(VertIdx, VertIdx)]) -> Vec<bool> {
+        let inside = self.constrained_interior(edges);
+        let mut free = vec![false; self.tri_len()];
+        for (idx, t) in self.triangles() {
+            free[idx.0] = !inside[idx.0] && !t.is_super();
+        }
+        free
+    }
+
+    /// A side of a triangle is open if it is not constrained, and the triangle across it is in
+    /// `free`.
+    pub fn side_open(
+        &self,
+        edges: &[(VertIdx, VertIdx)],
+        free: &[bool],
+        idx: TriIdx,
+        sub: SubIdx,
+    ) -> bool {
+        let t = self.tri(idx);
+        let v_from = t.vert(sub.cw());
+        let v_to = t.vert(sub);
+        if edges.binary_search(&(v_from, v_to)).is_ok()
+            || edges.binary_search(&(v_to, v_from)).is_ok()
+        {
+            return false;
+        }
+        match t.neighbor(sub) {
+            Some(n) => free[n.0],
+            None => false,
+        }
+    }
+
     fn visibility_tri<'a, 'b>(
         &'a self,
         edges: &[(VertIdx, VertIdx)],
@@ -1203,8 +1926,9 @@ struct VisibilityQuery<'a, 'b, T> {
     cw: &'a Point<T>,
 }
 
-/// Triangle representation
-#[derive(Clone)]
+/// Triangle representation. `TriangularNetwork` stores triangles as `u32` indices, this is a
+/// decoded copy of them.
+#[derive(Clone, Copy)]
 pub struct Triangle {
     /// list of vertex indices, in counterclockwise order
     pub vertices: [VertIdx; 3],
@@ -1245,20 +1969,6 @@ impl Triangle {
         self.neighbors[idx.0]
     }
 
-    fn neighbor_mut(&mut self, idx: SubIdx) -> &mut Option<TriIdx> {
-        &mut self.neighbors[idx.0]
-    }
-
-    fn update_neighbor(&mut self, idx_from: TriIdx, idx_to: TriIdx) -> bool {
-        for i in 0..3 {
-            if self.neighbors[i] == Some(idx_from) {
-                self.neighbors[i] = Some(idx_to);
-                return true;
-            }
-        }
-        todo!();
-    }
-
     #[allow(unused)]
     fn vertex_idx(&self, v_idx: VertIdx) -> Option<SubIdx> {
         self.vertices.iter().position(|p| *p == v_idx).map(SubIdx)
@@ -1272,6 +1982,22 @@ impl Triangle {
     }
 }
 
+/// Network with the points inserted, inside a super triangle large enough for the tests
+#[cfg(test)]
+pub(crate) fn test_net(points: &[Point<f64>]) -> TriangularNetwork<f64> {
+    let v = 1000.0;
+    let mut net = TriangularNetwork::new(
+        Point::new([-v, -v]),
+        Point::new([v, -v]),
+        Point::new([0.0, v]),
+    );
+    let mut r = usize::MAX;
+    for p in points {
+        net.insert(p, &mut r).unwrap();
+    }
+    net
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1299,4 +2025,231 @@ mod test {
             assert_eq!(net.locate(TriIdx(0), &Point::new([x, y])), expected);
         }
     }
+    #[test]
+    fn circumcircle() {
+        let p0 = Point::new([0.0, 0.0]);
+        let p1 = Point::new([4.0, 0.0]);
+        let p2 = Point::new([0.0, 2.0]);
+
+        let net = TriangularNetwork::new(p0, p1, p2);
+        let (center, r_sq) = net.circumcircle(TriIdx(0));
+        assert_eq!(center, Point::new([2.0, 1.0]));
+        assert_eq!(r_sq, 5.0);
+    }
+
+    // no vertex conflicts with the orthogonal circle of an adjacent non-super triangle
+    fn check_regular(net: &TriangularNetwork<f64>) {
+        for (idx, t) in net.triangles() {
+            for i in 0..3 {
+                let n = match t.neighbors[i] {
+                    Some(n) => n,
+                    None => continue,
+                };
+                let tn = net.tri(n);
+                if t.is_super() || tn.is_super() {
+                    continue;
+                }
+                let j = tn.neighbor_idx(idx).unwrap();
+                let opposite = tn.vert(j.ccw());
+                assert!(!net.power_test(t.vertices, net.vert(opposite), &net.weight(opposite)));
+            }
+        }
+    }
+
+    #[test]
+    fn insert_weighted() {
+        use crate::points_uniform;
+        use rand::prelude::*;
+
+        let view = 100.0;
+        let mut rng = StdRng::seed_from_u64(0);
+        let points = points_uniform(&mut rng, view, 200);
+
+        let new = || {
+            let v = view * 4.0;
+            TriangularNetwork::new(
+                Point::new([-v, -v]),
+                Point::new([v, -v]),
+                Point::new([0.0, v]),
+            )
+        };
+
+        // zero weights give the delaunay triangulation
+        let mut net = new();
+        let mut net_zero = new();
+        let mut r = usize::MAX;
+        for p in &points {
+            net.insert(p, &mut r).unwrap();
+            net_zero.insert_weighted(p, 0.0, &mut r).unwrap();
+        }
+        assert_eq!(net.edges(), net_zero.edges());
+
+        let mut net = new();
+        let mut redundant = 0;
+        for p in &points {
+            let w = rng.gen_range(0.0..100.0);
+            if net.insert_weighted(p, w, &mut r).unwrap().is_none() {
+                redundant += 1;
+            }
+        }
+        net.check_invariant("insert_weighted").unwrap();
+        check_regular(&net);
+
+        let remap = net.compact();
+        let removed = remap.verts.iter().filter(|v| v.is_none()).count();
+        assert!(redundant + removed > 0);
+        assert_eq!(net.vertices.len() + redundant + removed, points.len() + 3);
+        check_regular(&net);
+    }
+
+    #[test]
+    fn insert_weighted_redundant() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+
+        // covered by the heavy vertices around it, after they are inserted
+        let center = net
+            .insert_weighted(&Point::new([0.0, 0.0]), 0.0, &mut r)
+            .unwrap()
+            .unwrap();
+        for p in [[0.0, 3.0], [-3.0, -2.0], [3.0, -2.0]] {
+            net.insert_weighted(&Point::new(p), 16.0, &mut r)
+                .unwrap()
+                .unwrap();
+        }
+        assert!(net.triangles().all(|(_, t)| !t.vertices.contains(&center)));
+        assert_eq!(net.edges().len(), 3);
+        check_regular(&net);
+
+        assert_eq!(
+            net.insert_weighted(&Point::new([0.0, -1.0]), 0.0, &mut r)
+                .unwrap(),
+            None
+        );
+        assert!(net
+            .insert_weighted(&Point::new([0.0, -1.0]), 16.0, &mut r)
+            .unwrap()
+            .is_some());
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        splits: usize,
+        flips: usize,
+        cuts: usize,
+        constrains: Vec<(VertIdx, VertIdx)>,
+    }
+
+    impl NetworkObserver<f64> for Counter {
+        fn on_split(&mut self, net: &TriangularNetwork<f64>, vert: VertIdx, tris: &[TriIdx]) {
+            assert!(tris.iter().all(|t| net.tri(*t).vertices.contains(&vert)));
+            self.splits += 1;
+        }
+
+        fn on_flip(&mut self, net: &TriangularNetwork<f64>, t0: TriIdx, t1: TriIdx) {
+            assert!(net.tri(t0).neighbor_idx(t1).is_some());
+            self.flips += 1;
+        }
+
+        fn on_cut(&mut self, _net: &TriangularNetwork<f64>, _cut: &Cut) {
+            self.cuts += 1;
+        }
+
+        fn on_constrain(&mut self, _net: &TriangularNetwork<f64>, v_from: VertIdx, v_to: VertIdx) {
+            self.constrains.push((v_from, v_to));
+        }
+    }
+
+    #[test]
+    fn observer() {
+        let mut net = test_net(&[]);
+        let counter = Arc::new(Mutex::new(Counter::default()));
+        assert!(net.set_observer(Some(Box::new(counter.clone()))).is_none());
+
+        let mut r = usize::MAX;
+        let points = [[-10.0, 0.0], [10.0, 0.0], [0.0, 1.0], [0.0, -1.0]];
+        let verts = points.map(|p| net.insert(&Point::new(p), &mut r).unwrap());
+        // crosses the delaunay edge between (0, 1) and (0, -1)
+        net.constrain_edge(verts[0], verts[1]).unwrap();
+
+        // clones are not observed
+        let mut clone = net.clone();
+        clone.insert(&Point::new([1.0, 1.0]), &mut r).unwrap();
+
+        fn send_sync<S: Send + Sync>(_: &S) {}
+        send_sync(&net);
+
+        let counter = counter.lock().unwrap();
+        assert_eq!(counter.splits, points.len());
+        assert!(counter.flips > 0);
+        assert_eq!(counter.cuts, 1);
+        assert_eq!(counter.constrains, vec![(verts[0], verts[1])]);
+    }
+
+    #[test]
+    fn update_neighbor_missing() {
+        let mut net = test_net(&[Point::new([0.0, 0.0])]);
+        let n = net
+            .tri(TriIdx(0))
+            .neighbors
+            .iter()
+            .flatten()
+            .copied()
+            .next()
+            .unwrap();
+        assert!(net.update_neighbor(TriIdx(0), n, n));
+        assert!(!net.update_neighbor(TriIdx(0), TriIdx(0), n));
+    }
+
+    #[test]
+    fn compact() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+
+        // a vertex left unused, as by a removal
+        let unused = net.add_vert(Point::new([5.0, 5.0]));
+        let points = [[-10.0, 0.0], [10.0, 0.0], [0.0, 1.0], [0.0, -1.0]];
+        let verts = points.map(|p| net.insert(&Point::new(p), &mut r).unwrap());
+
+        // the cut reuses the slots of the crossed triangles
+        let live = net.tri_len();
+        net.constrain_edge(verts[0], verts[1]).unwrap();
+        assert_eq!(net.tri_len(), live);
+
+        // a slot freed, as by a removal
+        let free = net.add_tri();
+        net.free_tri(free);
+        assert!(net.is_free(free));
+        assert_eq!(net.tri_len(), live + 1);
+        assert_eq!(net.triangles().count(), live);
+
+        // free slots are reused
+        let mut reused = net.clone();
+        reused.insert(&Point::new([0.0, 10.0]), &mut r).unwrap();
+        assert_eq!(reused.tri_len(), live + 2);
+        assert!((0..reused.tri_len()).all(|idx| !reused.is_free(TriIdx(idx))));
+
+        let edges = net.edges();
+        let remap = net.compact();
+        assert_eq!(net.tri_len(), live);
+        assert_eq!(remap.tri(free), None);
+        assert_eq!(remap.vert(unused), None);
+        for v in &verts {
+            assert_eq!(remap.vert(*v), Some(VertIdx(v.0 - 1)));
+        }
+        assert_eq!(net.vertices.len(), 7);
+        net.check_invariant("compact").unwrap();
+
+        let edges = edges
+            .into_iter()
+            .map(|(a, b)| (remap.vert(a).unwrap(), remap.vert(b).unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(net.edges(), edges);
+        match net.locate_recursive(&Point::new([0.0, 1.0])) {
+            TriangularNetworkLocation::OnVertex(t, sub) => {
+                assert_eq!(net.tri(t).vert(sub), remap.vert(verts[2]).unwrap())
+            }
+            l => panic!("{:?}", l),
+        }
+    }
 }
@@ -0,0 +1,493 @@
+// http://www.sccg.sk/~samuelcik/dgs/quad_edge.pdf
+use crate::delaunay::*;
+use rgeometry::{data::*, Orientation, PolygonScalar};
+use std::collections::{BTreeMap, BTreeSet};
+
+type Result<T> = anyhow::Result<T>;
+
+const NONE: usize = usize::MAX;
+
+// directed edges of a quad-edge are stored next to each other, rotated by 90 degrees each
+fn rot(e: usize) -> usize {
+    (e & !3) | ((e + 1) & 3)
+}
+
+fn sym(e: usize) -> usize {
+    (e & !3) | ((e + 2) & 3)
+}
+
+fn rot_inv(e: usize) -> usize {
+    (e & !3) | ((e + 3) & 3)
+}
+
+#[derive(Default)]
+struct QuadEdges {
+    next: Vec<usize>,
+    org: Vec<usize>,
+    alive: Vec<bool>,
+}
+
+impl QuadEdges {
+    fn make_edge(&mut self, org: usize, dest: usize) -> usize {
+        let e = self.next.len();
+        self.next.extend([e, e + 3, e + 2, e + 1]);
+        self.org.extend([org, NONE, dest, NONE]);
+        self.alive.push(true);
+        e
+    }
+
+    fn onext(&self, e: usize) -> usize {
+        self.next[e]
+    }
+
+    fn oprev(&self, e: usize) -> usize {
+        rot(self.onext(rot(e)))
+    }
+
+    fn lnext(&self, e: usize) -> usize {
+        rot(self.onext(rot_inv(e)))
+    }
+
+    fn rprev(&self, e: usize) -> usize {
+        self.onext(sym(e))
+    }
+
+    fn org(&self, e: usize) -> usize {
+        self.org[e]
+    }
+
+    fn dest(&self, e: usize) -> usize {
+        self.org[sym(e)]
+    }
+
+    fn splice(&mut self, a: usize, b: usize) {
+        let alpha = rot(self.onext(a));
+        let beta = rot(self.onext(b));
+        self.next.swap(a, b);
+        self.next.swap(alpha, beta);
+    }
+
+    fn connect(&mut self, a: usize, b: usize) -> usize {
+        let e = self.make_edge(self.dest(a), self.org(b));
+        self.splice(e, self.lnext(a));
+        self.splice(sym(e), b);
+        e
+    }
+
+    fn delete(&mut self, e: usize) {
+        self.splice(e, self.oprev(e));
+        self.splice(sym(e), self.oprev(sym(e)));
+        self.alive[e >> 2] = false;
+    }
+}
+
+struct DivideConquer<'a, T> {
+    points: &'a [Point<T>],
+    edges: QuadEdges,
+}
+
+impl<'a, T: PolygonScalar> DivideConquer<'a, T> {
+    fn ccw(&self, a: usize, b: usize, c: usize) -> bool {
+        let o = Point::orient_along_direction(
+            &self.points[a],
+            Direction::Through(&self.points[b]),
+            &self.points[c],
+        );
+        o == Orientation::CounterClockWise
+    }
+
+    fn right_of(&self, x: usize, e: usize) -> bool {
+        self.ccw(x, self.edges.dest(e), self.edges.org(e))
+    }
+
+    fn left_of(&self, x: usize, e: usize) -> bool {
+        self.ccw(x, self.edges.org(e), self.edges.dest(e))
+    }
+
+    // `d` is strictly inside of the circle through `a`, `b` and `c`
+    fn in_circle(&self, a: usize, b: usize, c: usize, d: usize) -> bool {
+        let [pa, pb, pc, pd] = [a, b, c, d].map(|i| &self.points[i]);
+        match Point::orient_along_direction(pa, Direction::Through(pb), pc) {
+            Orientation::CounterClockWise => T::inside_circle(pa, pb, pc, pd),
+            Orientation::ClockWise => T::inside_circle(pa, pc, pb, pd),
+            Orientation::CoLinear => false,
+        }
+    }
+
+    // triangulate points in `lo..hi`, returns counterclockwise and clockwise convex hull edges
+    // out of the leftmost and the rightmost vertices
+    fn build(&mut self, lo: usize, hi: usize) -> (usize, usize) {
+        let len = hi - lo;
+        if len == 2 {
+            let a = self.edges.make_edge(lo, lo + 1);
+            return (a, sym(a));
+        }
+        if len == 3 {
+            let (s0, s1, s2) = (lo, lo + 1, lo + 2);
+            let a = self.edges.make_edge(s0, s1);
+            let b = self.edges.make_edge(s1, s2);
+            self.edges.splice(sym(a), b);
+
+            if self.ccw(s0, s1, s2) {
+                self.edges.connect(b, a);
+                return (a, sym(b));
+            } else if self.ccw(s0, s2, s1) {
+                let c = self.edges.connect(b, a);
+                return (sym(c), c);
+            } else {
+                return (a, sym(b));
+            }
+        }
+
+        let mid = lo + len / 2;
+        let (mut ldo, mut ldi) = self.build(lo, mid);
+        let (mut rdi, mut rdo) = self.build(mid, hi);
+
+        // lower common tangent of both halves
+        loop {
+            if self.left_of(self.edges.org(rdi), ldi) {
+                ldi = self.edges.lnext(ldi);
+            } else if self.right_of(self.edges.org(ldi), rdi) {
+                rdi = self.edges.rprev(rdi);
+            } else {
+                break;
+            }
+        }
+
+        let mut basel = self.edges.connect(sym(rdi), ldi);
+        if self.edges.org(ldi) == self.edges.org(ldo) {
+            ldo = sym(basel);
+        }
+        if self.edges.org(rdi) == self.edges.org(rdo) {
+            rdo = basel;
+        }
+
+        // merge upwards from the tangent
+        loop {
+            let valid = |dc: &Self, e: usize, basel: usize| dc.right_of(dc.edges.dest(e), basel);
+
+            let mut lcand = self.edges.onext(sym(basel));
+            if valid(self, lcand, basel) {
+                while self.in_circle(
+                    self.edges.dest(basel),
+                    self.edges.org(basel),
+                    self.edges.dest(lcand),
+                    self.edges.dest(self.edges.onext(lcand)),
+                ) {
+                    let t = self.edges.onext(lcand);
+                    self.edges.delete(lcand);
+                    lcand = t;
+                }
+            }
+
+            let mut rcand = self.edges.oprev(basel);
+            if valid(self, rcand, basel) {
+                while self.in_circle(
+                    self.edges.dest(basel),
+                    self.edges.org(basel),
+                    self.edges.dest(rcand),
+                    self.edges.dest(self.edges.oprev(rcand)),
+                ) {
+                    let t = self.edges.oprev(rcand);
+                    self.edges.delete(rcand);
+                    rcand = t;
+                }
+            }
+
+            let lvalid = valid(self, lcand, basel);
+            let rvalid = valid(self, rcand, basel);
+            if !lvalid && !rvalid {
+                break;
+            }
+
+            basel = if !lvalid
+                || (rvalid
+                    && self.in_circle(
+                        self.edges.dest(lcand),
+                        self.edges.org(lcand),
+                        self.edges.org(rcand),
+                        self.edges.dest(rcand),
+                    )) {
+                self.edges.connect(rcand, sym(basel))
+            } else {
+                self.edges.connect(sym(basel), sym(lcand))
+            };
+        }
+
+        (ldo, rdo)
+    }
+
+    // counterclockwise triangles of the triangulation
+    fn triangles(&self) -> Vec<[usize; 3]> {
+        let mut visited = vec![false; self.edges.next.len()];
+        let mut out = Vec::new();
+        for (q, alive) in self.edges.alive.iter().enumerate() {
+            if !alive {
+                continue;
+            }
+            for e in [q * 4, q * 4 + 2] {
+                if visited[e] {
+                    continue;
+                }
+                visited[e] = true;
+
+                let e1 = self.edges.lnext(e);
+                let e2 = self.edges.lnext(e1);
+                if self.edges.lnext(e2) != e {
+                    continue;
+                }
+                let [v0, v1, v2] = [e, e1, e2].map(|e| self.edges.org(e));
+                if !self.ccw(v0, v1, v2) {
+                    continue;
+                }
+                visited[e1] = true;
+                visited[e2] = true;
+                out.push([v0, v1, v2]);
+            }
+        }
+        out
+    }
+}
+
+// directed edges of triangles
+fn tri_edges(triangles: impl Iterator<Item = (TriIdx, Triangle)>) -> BTreeSet<(VertIdx, VertIdx)> {
+    let mut edges = BTreeSet::new();
+    for (_, t) in triangles {
+        for i in 0..3 {
+            let sub = SubIdx(i);
+            edges.insert((t.vert(sub.cw()), t.vert(sub)));
+        }
+    }
+    edges
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    /// Build a network of `points` inside of the super triangle `p0`, `p1`, `p2`, with
+    /// divide-and-conquer Delaunay triangulation (Guibas-Stolfi) instead of incremental
+    /// insertion. Returns the network and `VertIdx` of each point; duplicated points share a
+    /// vertex.
+    pub fn from_points(
+        p0: Point<T>,
+        p1: Point<T>,
+        p2: Point<T>,
+        points: &[Point<T>],
+    ) -> Result<(Self, Vec<VertIdx>)> {
+        let mut order = (0..points.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let [ax, ay] = &points[*a].array;
+            let [bx, by] = &points[*b].array;
+            ax.partial_cmp(bx)
+                .unwrap()
+                .then(ay.partial_cmp(by).unwrap())
+        });
+
+        let mut sorted: Vec<Point<T>> = Vec::with_capacity(points.len());
+        let mut mapping = vec![VertIdx(0); points.len()];
+        for i in order {
+            if sorted.last() != Some(&points[i]) {
+                sorted.push(points[i].clone());
+            }
+            mapping[i] = VertIdx(sorted.len() + 2);
+        }
+
+        let mut net = Self::new(p0, p1, p2);
+        let mut r = usize::MAX;
+
+        let tris = if sorted.len() >= 3 {
+            let mut dc = DivideConquer {
+                points: &sorted,
+                edges: QuadEdges::default(),
+            };
+            dc.build(0, sorted.len());
+            dc.triangles()
+        } else {
+            vec![]
+        };
+
+        // less than three points, or all points are colinear
+        if tris.is_empty() {
+            for p in &sorted {
+                net.insert(p, &mut r)?;
+            }
+            return Ok((net, mapping));
+        }
+
+        let mut edges = BTreeSet::new();
+        for t in &tris {
+            for i in 0..3 {
+                edges.insert((t[i], t[(i + 1) % 3]));
+            }
+        }
+        let hull = edges
+            .iter()
+            .filter(|(v0, v1)| !edges.contains(&(*v1, *v0)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // triangulate between the convex hull and the super triangle, by inserting hull vertices
+        // and forcing hull edges
+        let mut hull_verts = hull.iter().map(|(v, _)| *v).collect::<Vec<_>>();
+        hull_verts.sort();
+        hull_verts.dedup();
+
+        let mut remap = vec![VertIdx(0), VertIdx(1), VertIdx(2)];
+        let mut hull_idx = BTreeMap::new();
+        for v in hull_verts {
+            let idx = net.insert(&sorted[v], &mut r)?;
+            if remap.len() <= idx.0 {
+                remap.resize(idx.0 + 1, VertIdx(0));
+            }
+            remap[idx.0] = VertIdx(v + 3);
+            hull_idx.insert(v, idx);
+        }
+
+        // hull edges missing before any constraint. Constraining one of them may create another,
+        // which `constrain_edge` then leaves as it is
+        let net_edges = tri_edges(net.triangles());
+        let missing = hull
+            .iter()
+            .map(|(v0, v1)| (hull_idx[v0], hull_idx[v1]))
+            .filter(|edge| !net_edges.contains(edge))
+            .collect::<Vec<_>>();
+        for (idx0, idx1) in missing {
+            net.constrain_edge(idx0, idx1)?;
+        }
+
+        let mut vertices = net.vertices[..3].to_vec();
+        vertices.extend(sorted);
+
+        let mut triangles = Vec::with_capacity(net.tri_len() + tris.len());
+        for (_, t) in net.triangles().filter(|(_, t)| t.is_super()) {
+            triangles.push(Triangle {
+                vertices: t.vertices.map(|v| remap[v.0]),
+                neighbors: [None; 3],
+            });
+        }
+        for t in tris {
+            triangles.push(Triangle {
+                vertices: t.map(|v| VertIdx(v + 3)),
+                neighbors: [None; 3],
+            });
+        }
+
+        // link triangles through shared edges
+        let mut owners = BTreeMap::new();
+        for (idx, t) in triangles.iter().enumerate() {
+            for i in 0..3 {
+                let sub = SubIdx(i);
+                owners.insert((t.vert(sub.cw()), t.vert(sub)), TriIdx(idx));
+            }
+        }
+        for t in &mut triangles {
+            for i in 0..3 {
+                let sub = SubIdx(i);
+                t.neighbors[i] = owners.get(&(t.vert(sub), t.vert(sub.cw()))).copied();
+            }
+        }
+
+        Ok((Self::from_triangles(vertices, &triangles), mapping))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{points_grid, points_uniform};
+    use rand::prelude::*;
+
+    fn super_tri(view: f64) -> [Point<f64>; 3] {
+        let v = view * 4.0;
+        [
+            Point::new([-v, -v]),
+            Point::new([v, -v]),
+            Point::new([0.0, v]),
+        ]
+    }
+
+    // neighbors are consistent, and no vertex is inside of the circumcircle of an adjacent
+    // non-super triangle
+    fn check_delaunay(net: &TriangularNetwork<f64>) {
+        for (idx, t) in net.triangles() {
+            for i in 0..3 {
+                let sub = SubIdx(i);
+                let n = match t.neighbors[i] {
+                    Some(n) => n,
+                    None => continue,
+                };
+                let tn = net.tri(n);
+                let j = tn.neighbors.iter().position(|t| *t == Some(idx)).unwrap();
+                assert_eq!(tn.vert(SubIdx(j)), t.vert(sub.cw()));
+                assert_eq!(tn.vert(SubIdx(j).cw()), t.vert(sub));
+
+                if t.is_super() || tn.is_super() {
+                    continue;
+                }
+                let [p0, p1, p2] = t.vertices.map(|v| net.vert(v));
+                let opposite = net.vert(tn.vert(SubIdx(j).ccw()));
+                assert!(!f64::inside_circle(p0, p1, p2, opposite));
+            }
+        }
+    }
+
+    #[test]
+    fn from_points_uniform() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let view = 100.0;
+        let points = points_uniform(&mut rng, view, 500);
+
+        let [p0, p1, p2] = super_tri(view);
+        let (net, mapping) = TriangularNetwork::from_points(p0, p1, p2, &points).unwrap();
+        check_delaunay(&net);
+
+        for (p, v) in points.iter().zip(mapping) {
+            assert_eq!(net.vert(v), p);
+        }
+
+        // 2n - 2 - h triangles of the points, and n + 1 + h triangles outside of the hull
+        assert_eq!(net.tri_len(), 2 * (points.len() + 3) - 5);
+    }
+
+    #[test]
+    fn from_points_degenerate() {
+        let view = 10.0;
+        let [p0, p1, p2] = super_tri(view);
+
+        // grid has cocircular points, and duplicated points
+        let mut points = points_grid(view, 5);
+        points.extend(points_grid(view, 5));
+        let (net, mapping) = TriangularNetwork::from_points(p0, p1, p2, &points).unwrap();
+        check_delaunay(&net);
+        assert_eq!(net.vertices.len(), 25 + 3);
+        assert_eq!(mapping[0], mapping[25]);
+
+        // colinear points
+        let points = (0..5)
+            .map(|i| Point::new([i as f64, i as f64]))
+            .collect::<Vec<_>>();
+        let (net, _) = TriangularNetwork::from_points(p0, p1, p2, &points).unwrap();
+        check_delaunay(&net);
+        assert_eq!(net.vertices.len(), 5 + 3);
+    }
+
+    #[test]
+    fn from_points_insert() {
+        let view = 10.0;
+        let [p0, p1, p2] = super_tri(view);
+        let points = points_grid(view, 4);
+        let (mut net, _) = TriangularNetwork::from_points(p0, p1, p2, &points).unwrap();
+
+        let mut r = usize::MAX;
+        let v0 = net.insert(&Point::new([1.1, 2.3]), &mut r).unwrap();
+        let v1 = net.find_vert(&points[0]).unwrap();
+        net.constrain_edge(v0, v1).unwrap();
+        check_neighbors(&net);
+    }
+
+    fn check_neighbors(net: &TriangularNetwork<f64>) {
+        for (idx, t) in net.triangles() {
+            for n in t.neighbors.iter().flatten() {
+                assert!(net.tri(*n).neighbors.contains(&Some(idx)));
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::Rect;
+
+/// Rectangles of the boolean2 demo, as `[x, y, w, h, heading]` for `Rect`. Their sides nearly
+/// coincide, which breaks `f64` boolean operations without snap rounding.
+pub const BOOLEAN2_RECTS: &[[f64; 5]] = &[
+    [
+        -123.54193216787064,
+        -52.91399622244809,
+        7.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        -17.937753007736486,
+        -98.44154111468106,
+        82.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        42.874641733514906,
+        93.13573479724495,
+        102.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        -88.54424874680433,
+        40.89518325993956,
+        2.5,
+        102.5,
+        -0.4070386786719689,
+    ],
+    [
+        95.11519327082028,
+        -38.283155683074284,
+        2.5,
+        102.5,
+        -0.4070386786719689,
+    ],
+    [
+        9.611163294907207,
+        -110.31829195613312,
+        52.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        49.200332766414135,
+        -18.488570947320827,
+        52.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        -34.3242387359236,
+        -85.93222116993906,
+        2.5,
+        7.5,
+        -0.4070386786719689,
+    ],
+    [
+        -8.591278579444097,
+        -26.242902514211057,
+        2.5,
+        32.5,
+        -0.4070386786719689,
+    ],
+    [
+        75.32060853506682,
+        -84.19801618748045,
+        2.5,
+        52.5,
+        -0.4070386786719689,
+    ],
+    [
+        49.200332766414135,
+        -18.488570947320827,
+        52.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        88.78950223792106,
+        73.34115006149148,
+        52.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        5.264930735583327,
+        5.89749983887325,
+        2.5,
+        7.5,
+        -0.4070386786719689,
+    ],
+    [
+        30.997890892062827,
+        65.58681849460125,
+        2.5,
+        32.5,
+        -0.4070386786719689,
+    ],
+    [
+        114.90977800657376,
+        7.631704821331866,
+        2.5,
+        52.5,
+        -0.4070386786719689,
+    ],
+    [
+        -105.17598796610817,
+        -60.83183011674947,
+        17.5,
+        2.5,
+        -0.4070386786719689,
+    ],
+    [
+        -26.406404841622212,
+        -67.56627696817658,
+        2.5,
+        17.5,
+        -0.4070386786719689,
+    ],
+    [
+        13.182764629884712,
+        24.263444040635708,
+        2.5,
+        17.5,
+        -0.4070386786719689,
+    ],
+    [141.74915755470903, 100.0, 5.0, 5.0, -0.616137379020819],
+];
+
+/// `BOOLEAN2_RECTS` as `Rect`s
+pub fn boolean2_rects() -> Vec<Rect> {
+    BOOLEAN2_RECTS
+        .iter()
+        .map(|[x, y, w, h, heading]| Rect::new(*w, *h).pos(*x, *y).rot(*heading))
+        .collect()
+}
@@ -0,0 +1,319 @@
+use crate::cross;
+use crate::delaunay::*;
+use rgeometry::data::*;
+use std::collections::BinaryHeap;
+
+type Result<T> = anyhow::Result<T>;
+
+// a triangle collapses at `s`, ordered by the earliest first
+struct Collapse {
+    s: f64,
+    tri: TriIdx,
+    version: u32,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .s
+            .partial_cmp(&self.s)
+            .unwrap()
+            .then(other.tri.cmp(&self.tri))
+            .then(other.version.cmp(&self.version))
+    }
+}
+
+fn sub(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+/// Triangular network whose vertices move linearly from `t0` to `t1`. Triangles are kept
+/// counterclockwise by flipping the side a vertex crosses, at the time the triangle becomes
+/// degenerate. The network stays a valid triangulation, but not a delaunay triangulation.
+/// Constraints are not preserved.
+pub struct KineticNetwork {
+    net: TriangularNetwork<f64>,
+    // positions at `t0` and `t1`, indexed by `VertIdx`
+    from: Vec<Point<f64>>,
+    to: Vec<Point<f64>>,
+    t0: f64,
+    t1: f64,
+    // current time, in [0, 1] between `t0` and `t1`
+    s: f64,
+    // incremented when a triangle changes, to skip stale collapses
+    version: Vec<u32>,
+    collapses: BinaryHeap<Collapse>,
+}
+
+impl KineticNetwork {
+    /// Start moving the vertices of `net` at `t0`, from their current positions. Each vertex in
+    /// `moves` arrives at its position at `t1`, and other vertices stay still. Fails if a super
+    /// vertex moves, or a position at `t1` is not inside the super triangle. Trajectories are
+    /// linear, so vertices then stay inside it. For piecewise linear trajectories, start another
+    /// `KineticNetwork` from `into_net` at the end of each piece.
+    pub fn new(
+        net: TriangularNetwork<f64>,
+        moves: &[(VertIdx, Point<f64>)],
+        t0: f64,
+        t1: f64,
+    ) -> Result<Self> {
+        if t1 <= t0 {
+            anyhow::bail!("kinetic: empty time range, t0={}, t1={}", t0, t1);
+        }
+
+        let from = net.vertices.clone();
+        let mut to = from.clone();
+        for (v, p) in moves {
+            if v.is_super() {
+                anyhow::bail!("kinetic: super vertex {:?} can not move", v);
+            }
+            // super vertices are counterclockwise
+            let inside = (0..3).all(|i| {
+                let a = from[i].array;
+                let b = from[(i + 1) % 3].array;
+                cross(sub(b, a), sub(p.array, a)) > 0.0
+            });
+            if !inside {
+                anyhow::bail!("kinetic: {:?} leaves the super triangle to {:?}", v, p);
+            }
+            to[v.0] = *p;
+        }
+
+        let mut kinetic = Self {
+            version: vec![0; net.tri_len()],
+            net,
+            from,
+            to,
+            t0,
+            t1,
+            s: 0.0,
+            collapses: BinaryHeap::new(),
+        };
+        for idx in 0..kinetic.net.tri_len() {
+            kinetic.schedule(TriIdx(idx));
+        }
+        Ok(kinetic)
+    }
+
+    pub fn net(&self) -> &TriangularNetwork<f64> {
+        &self.net
+    }
+
+    pub fn into_net(self) -> TriangularNetwork<f64> {
+        self.net
+    }
+
+    /// Current time
+    pub fn time(&self) -> f64 {
+        self.t0 + self.s * (self.t1 - self.t0)
+    }
+
+    /// Position of the vertex at time `t`
+    pub fn position(&self, v: VertIdx, t: f64) -> Point<f64> {
+        let s = ((t - self.t0) / (self.t1 - self.t0)).clamp(0.0, 1.0);
+        self.position_at(v, s)
+    }
+
+    fn position_at(&self, v: VertIdx, s: f64) -> Point<f64> {
+        let [x0, y0] = self.from[v.0].array;
+        let [x1, y1] = self.to[v.0].array;
+        Point::new([x0 + (x1 - x0) * s, y0 + (y1 - y0) * s])
+    }
+
+    // earliest time after `self.s` when the triangle turns clockwise
+    fn collapse_time(&self, idx: TriIdx) -> Option<f64> {
+        let [a, b, c] = self.net.tri(idx).vertices;
+        let delta = |v: VertIdx| sub(self.to[v.0].array, self.from[v.0].array);
+
+        let u0 = sub(self.from[b.0].array, self.from[a.0].array);
+        let w0 = sub(self.from[c.0].array, self.from[a.0].array);
+        let du = sub(delta(b), delta(a));
+        let dw = sub(delta(c), delta(a));
+
+        // signed area, as A s^2 + B s + C
+        let qa = cross(du, dw);
+        let qb = cross(u0, dw) + cross(du, w0);
+        let qc = cross(u0, w0);
+
+        let mut roots = Vec::with_capacity(2);
+        if qa == 0.0 {
+            if qb != 0.0 {
+                roots.push(-qc / qb);
+            }
+        } else {
+            let disc = qb * qb - 4.0 * qa * qc;
+            if disc >= 0.0 {
+                let sqrt = disc.sqrt();
+                roots.push((-qb - sqrt) / (2.0 * qa));
+                roots.push((-qb + sqrt) / (2.0 * qa));
+            }
+        }
+
+        roots
+            .into_iter()
+            .filter(|s| *s > self.s && *s <= 1.0 && 2.0 * qa * s + qb < 0.0)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
+    fn schedule(&mut self, idx: TriIdx) {
+        if self.net.is_free(idx) {
+            return;
+        }
+        if let Some(s) = self.collapse_time(idx) {
+            self.collapses.push(Collapse {
+                s,
+                tri: idx,
+                version: self.version[idx.0],
+            });
+        }
+    }
+
+    // the vertex which crosses the opposite side of the degenerate triangle, lying between
+    // the other two
+    fn crossing_vertex(&self, idx: TriIdx, s: f64) -> SubIdx {
+        let t = self.net.tri(idx);
+        let p = t.vertices.map(|v| self.position_at(v, s).array);
+        (0..3)
+            .map(SubIdx)
+            .map(|sub| {
+                let d0 = self::sub(p[sub.ccw().0], p[sub.0]);
+                let d1 = self::sub(p[sub.cw().0], p[sub.0]);
+                (d0[0] * d1[0] + d0[1] * d1[1], sub)
+            })
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .unwrap()
+            .1
+    }
+
+    /// Move the vertices to time `t`, clamped to `t1`, flipping collapsed triangles on the way.
+    /// Returns the number of flips. Fails if `t` is before the current time.
+    pub fn advance(&mut self, t: f64) -> Result<usize> {
+        let s_end = ((t - self.t0) / (self.t1 - self.t0)).min(1.0);
+        if s_end < self.s {
+            anyhow::bail!("kinetic: can not go back from {} to {}", self.time(), t);
+        }
+
+        let mut flips = 0;
+        while let Some(collapse) = self.collapses.peek() {
+            if collapse.s > s_end {
+                break;
+            }
+            let Collapse { s, tri, version } = self.collapses.pop().unwrap();
+            if self.net.is_free(tri) || self.version[tri.0] != version {
+                continue;
+            }
+            self.s = s;
+
+            let sub = self.crossing_vertex(tri, s);
+            let neighbor = self.net.flip(tri, sub.cw())?;
+            flips += 1;
+
+            for idx in [tri, neighbor] {
+                self.version[idx.0] += 1;
+                self.schedule(idx);
+            }
+        }
+
+        self.s = s_end;
+        for v in 0..self.net.vertices.len() {
+            self.net.vertices[v] = self.position_at(VertIdx(v), s_end);
+        }
+        Ok(flips)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::points_uniform;
+    use rand::prelude::*;
+    use rgeometry::Orientation;
+
+    fn check_ccw(net: &TriangularNetwork<f64>) {
+        for (_, t) in net.triangles() {
+            let [p0, p1, p2] = t.vertices.map(|v| net.vert(v));
+            let o = Point::orient_along_direction(p0, Direction::Through(p1), p2);
+            assert_eq!(o, Orientation::CounterClockWise, "{:?}", t);
+        }
+    }
+
+    #[test]
+    fn kinetic_cross_edge() {
+        let points = [[0.0, 0.0], [4.0, 0.0], [2.0, 2.0], [2.0, -2.0]].map(Point::new);
+        let net = test_net(&points);
+        let v = net.find_vert(&points[2]).unwrap();
+
+        // the top vertex moves through the side between the left and the right vertices
+        let mut kinetic =
+            KineticNetwork::new(net, &[(v, Point::new([2.0, -1.0]))], 0.0, 2.0).unwrap();
+        assert_eq!(kinetic.advance(0.5).unwrap(), 0);
+        check_ccw(kinetic.net());
+
+        assert!(kinetic.advance(2.0).unwrap() > 0);
+        assert_eq!(kinetic.time(), 2.0);
+        assert_eq!(*kinetic.net().vert(v), Point::new([2.0, -1.0]));
+        check_ccw(kinetic.net());
+
+        assert!(kinetic.advance(1.0).is_err());
+    }
+
+    #[test]
+    fn kinetic_leave_super() {
+        let points = [[0.0, 0.0], [4.0, 0.0], [2.0, 2.0]].map(Point::new);
+        let net = test_net(&points);
+        let v = net.find_vert(&points[2]).unwrap();
+
+        // the super triangle of `test_net` is within 1000
+        assert!(
+            KineticNetwork::new(net.clone(), &[(v, Point::new([2.0, 999.0]))], 0.0, 1.0).is_err()
+        );
+        assert!(
+            KineticNetwork::new(net.clone(), &[(v, Point::new([2.0, -1000.0]))], 0.0, 1.0).is_err()
+        );
+        assert!(KineticNetwork::new(net, &[(v, Point::new([2.0, 900.0]))], 0.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn kinetic_uniform() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let view = 100.0;
+        let points = points_uniform(&mut rng, view, 100);
+        let net = test_net(&points);
+
+        let moves = (3..net.vertices.len())
+            .map(|v| {
+                let [x, y] = net.vertices[v].array;
+                let dx = rng.gen_range(-view..view);
+                let dy = rng.gen_range(-view..view);
+                (VertIdx(v), Point::new([x + dx, y + dy]))
+            })
+            .collect::<Vec<_>>();
+
+        let mut kinetic = KineticNetwork::new(net, &moves, 0.0, 1.0).unwrap();
+        let mut flips = 0;
+        for i in 1..=10 {
+            flips += kinetic.advance(i as f64 / 10.0).unwrap();
+            check_ccw(kinetic.net());
+        }
+        assert!(flips > 0);
+
+        let net = kinetic.into_net();
+        for (v, p) in moves {
+            assert_eq!(*net.vert(v), p);
+        }
+    }
+}
@@ -1,14 +1,32 @@
 pub mod aabb;
 pub mod boolean;
+pub mod clearance;
+pub mod convex;
 pub mod delaunay;
+pub mod divide_conquer;
+pub mod fixtures;
 pub mod intersections;
+pub mod kinetic;
+pub mod measure;
+pub mod minkowski;
+pub mod offset;
+pub mod power;
+pub mod proximity;
 pub mod raster;
+pub mod rings;
+pub mod rooms;
+pub mod shape;
+pub mod skeleton;
+pub mod snap;
+pub mod viewshed;
 pub mod visibility;
+pub mod walk;
 
 use boolean::*;
 use delaunay::*;
 use rand::prelude::*;
 use rgeometry::data::*;
+use rgeometry::PolygonScalar;
 use visibility::*;
 
 pub fn visibility_limit(vis: &mut VisibilityResult<f64>, limit: f64) {
@@ -93,6 +111,24 @@ fn lerp_pf64(p0: &Point<f64>, p1: &Point<f64>, t: f64) -> Point<f64> {
     ])
 }
 
+// z component of the cross product of two vectors
+pub(crate) fn cross<T: PolygonScalar>(a: [T; 2], b: [T; 2]) -> T {
+    let [ax, ay] = a;
+    let [bx, by] = b;
+    ax * by - ay * bx
+}
+
+// twice the signed area enclosed by the sides, positive if counterclockwise
+pub(crate) fn area_2x<'a, T: PolygonScalar + 'a>(
+    sides: impl IntoIterator<Item = (&'a Point<T>, &'a Point<T>)>,
+) -> T {
+    let mut sum = T::from_constant(0);
+    for (p, q) in sides {
+        sum += cross(p.array.clone(), q.array.clone());
+    }
+    sum
+}
+
 fn points_along(src: &Point<f64>, dst: &Point<f64>, subdivide: usize, out: &mut Vec<Point<f64>>) {
     for i in 0..subdivide {
         let t = ((i + 1) as f64) / (subdivide as f64);
@@ -258,63 +294,25 @@ pub fn gen_rects<R: Rng>(rng: &mut R, view: f64, count: usize) -> Vec<Rect> {
     rects
 }
 
+/// Network built by `try_build_net`, and its constrained edges
+pub type ConstrainedNet = (TriangularNetwork<f64>, Vec<(VertIdx, VertIdx)>);
+
 pub fn build_net(
     view: f64,
     sx: &SimplicalChain<f64>,
     cut: bool,
 ) -> (TriangularNetwork<f64>, Vec<(VertIdx, VertIdx)>) {
-    use std::collections::*;
-
-    let v = view * 4.0;
-    let mut net = TriangularNetwork::new(
-        Point::new([-v, -v]),
-        Point::new([v, -v]),
-        Point::new([0.0, v]),
-    );
-
-    let mut h = BTreeMap::new();
-    let mut r = std::usize::MAX;
-    for s in &sx.simplices {
-        match net.insert(&s.dst, &mut r) {
-            Ok(idx) => {
-                let mut p = s.dst;
-                h.insert(p, idx);
-                if p.array[0] == -0.0 {
-                    p.array[0] = 0.0;
-                    h.insert(p, idx);
-                }
-
-                if p.array[0] == -0.0 {
-                    p.array[0] = 0.0;
-                    h.insert(p, idx);
-                }
-            }
-            Err(e) => {
-                eprintln!("TriangularNetwork::insert: {:?}", e);
-                return (net, vec![]);
-            }
-        }
-    }
-
-    let constraints = if cut {
-        let mut constraints = Vec::with_capacity(sx.simplices.len());
-        for s in &sx.simplices {
-            let idx0 = h.get(&s.src).unwrap();
-            let idx1 = h.get(&s.dst).unwrap();
-
-            if let Err(e) = net.constrain_edge(*idx0, *idx1) {
-                eprintln!("failed to cut: cut={:?}, e={:?}", cut, e);
-                break;
-            }
-            constraints.push((*idx0, *idx1));
-        }
-        constraints.sort();
-        constraints
-    } else {
-        vec![]
-    };
+    snap::build_net_snapped(view, sx, cut, snap::Snap::Exact)
+}
 
-    (net, constraints)
+/// `build_net`, but fails instead of returning a partial network when a point can not be
+/// inserted or a simplex can not be constrained
+pub fn try_build_net(
+    view: f64,
+    sx: &SimplicalChain<f64>,
+    cut: bool,
+) -> anyhow::Result<ConstrainedNet> {
+    snap::try_build_net_snapped(view, sx, cut, snap::Snap::Exact)
 }
 
 #[cfg(test)]
@@ -0,0 +1,141 @@
+use crate::area_2x;
+use crate::boolean::SimplicalChain;
+use crate::rings::{crossing, on_segment};
+use rgeometry::{data::*, PolygonScalar};
+
+/// Location of a point relative to a chain, see `SimplicalChain::locate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Outside,
+    OnBoundary,
+}
+
+impl<T: PolygonScalar + Clone> SimplicalChain<T> {
+    // twice the signed area
+    fn signed_area_2x(&self) -> T {
+        area_2x(self.simplices.iter().map(|s| (&s.src, &s.dst)))
+    }
+
+    /// Signed area, positive if the chain is counterclockwise. Areas covered several times are
+    /// counted with their winding number.
+    pub fn signed_area(&self) -> T {
+        self.signed_area_2x() / T::from_constant(2)
+    }
+
+    /// Centroid of the area, or `None` if the signed area is zero
+    pub fn centroid(&self) -> Option<Point<T>> {
+        let zero = T::from_constant(0);
+        let area = self.signed_area_2x();
+        if area == zero {
+            return None;
+        }
+
+        let mut cx = zero.clone();
+        let mut cy = zero;
+        for s in &self.simplices {
+            let [x0, y0] = s.src.array.clone();
+            let [x1, y1] = s.dst.array.clone();
+            let c = x0.clone() * y1.clone() - x1.clone() * y0.clone();
+            cx += (x0 + x1) * c.clone();
+            cy += (y0 + y1) * c;
+        }
+        let denom = area * T::from_constant(3);
+        Some(Point::new([cx / denom.clone(), cy / denom]))
+    }
+
+    /// Winding number of the chain around `p`. Simplices crossing the ray to the right of `p`
+    /// upwards count 1, and downwards -1, so the result is only meaningful off the boundary.
+    pub fn winding(&self, p: &Point<T>) -> i32 {
+        self.simplices
+            .iter()
+            .map(|s| crossing(&s.src, &s.dst, p))
+            .sum()
+    }
+
+    /// Locate `p` in the chain. Unlike `characteristic`, points on a simplex are `OnBoundary`,
+    /// and other points are `Inside` if the winding number is not zero.
+    pub fn locate(&self, p: &Point<T>) -> Location {
+        if self.simplices.iter().any(|s| on_segment(&s.src, &s.dst, p)) {
+            Location::OnBoundary
+        } else if self.winding(p) != 0 {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+}
+
+impl SimplicalChain<f64> {
+    /// Total length of the simplices
+    pub fn perimeter(&self) -> f64 {
+        self.simplices
+            .iter()
+            .map(|s| {
+                let dist: f64 = s.src.squared_euclidean_distance(&s.dst);
+                dist.sqrt()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> SimplicalChain<f64> {
+        let points = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+        let p = Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap();
+        SimplicalChain::from_polygon(&p)
+    }
+
+    #[test]
+    fn measure_square() {
+        let sx = square(1.0, 1.0, 3.0, 2.0);
+        assert_eq!(sx.signed_area(), 2.0);
+        assert_eq!(sx.perimeter(), 6.0);
+        assert_eq!(sx.centroid(), Some(Point::new([2.0, 1.5])));
+
+        let mut reversed = sx.clone();
+        for s in &mut reversed.simplices {
+            std::mem::swap(&mut s.src, &mut s.dst);
+        }
+        assert_eq!(reversed.signed_area(), -2.0);
+        assert_eq!(reversed.centroid(), Some(Point::new([2.0, 1.5])));
+        assert_eq!(reversed.winding(&Point::new([2.0, 1.5])), -1);
+
+        assert_eq!(SimplicalChain::<f64>::default().centroid(), None);
+    }
+
+    #[test]
+    fn measure_hole() {
+        // 4x4 frame around a 2x2 hole on the right
+        let sx = square(0.0, 0.0, 4.0, 4.0).subtract(&square(2.0, 1.0, 4.0, 3.0));
+        assert_eq!(sx.signed_area(), 12.0);
+        assert_eq!(sx.perimeter(), 16.0 + 4.0);
+        // (16 * 2 - 4 * 3) / 12
+        assert_eq!(sx.centroid(), Some(Point::new([5.0 / 3.0, 2.0])));
+
+        assert_eq!(sx.locate(&Point::new([1.0, 2.0])), Location::Inside);
+        assert_eq!(sx.locate(&Point::new([3.0, 2.0])), Location::Outside);
+        assert_eq!(sx.locate(&Point::new([5.0, 2.0])), Location::Outside);
+        assert_eq!(sx.locate(&Point::new([2.0, 2.0])), Location::OnBoundary);
+        assert_eq!(sx.locate(&Point::new([4.0, 0.5])), Location::OnBoundary);
+        assert_eq!(sx.locate(&Point::new([0.0, 0.0])), Location::OnBoundary);
+
+        // `characteristic` does not tell the boundary apart
+        assert_eq!(sx.characteristic(&Point::new([2.0, 2.0])), 1.0);
+    }
+
+    #[test]
+    fn measure_winding() {
+        // overlapping squares, not merged
+        let mut sx = square(0.0, 0.0, 2.0, 2.0);
+        sx.simplices.extend(square(1.0, 1.0, 3.0, 3.0).simplices);
+        assert_eq!(sx.winding(&Point::new([0.5, 0.5])), 1);
+        assert_eq!(sx.winding(&Point::new([1.5, 1.5])), 2);
+        assert_eq!(sx.winding(&Point::new([3.5, 1.5])), 0);
+        assert_eq!(sx.signed_area(), 8.0);
+        assert_eq!(sx.locate(&Point::new([1.5, 1.5])), Location::Inside);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::boolean::{Simplex, SimplicalChain};
+use crate::convex::convex_decomposition;
+use crate::{area_2x, cross};
+use rgeometry::{data::*, Orientation};
+
+// counterclockwise points of the polygon, starting at the lowest one
+fn ccw_from_lowest(p: &Polygon<f64>) -> Vec<[f64; 2]> {
+    let mut points = p.iter().map(|p| p.array).collect::<Vec<_>>();
+    if area_2x(p.iter_boundary_edges().map(|e| (e.src, e.dst))) < 0.0 {
+        points.reverse();
+    }
+    let lowest = (0..points.len())
+        .min_by(|a, b| {
+            let (a, b) = (points[*a], points[*b]);
+            (a[1], a[0]).partial_cmp(&(b[1], b[0])).unwrap()
+        })
+        .unwrap_or(0);
+    points.rotate_left(lowest);
+    points
+}
+
+fn is_convex(p: &Polygon<f64>) -> bool {
+    let points = ccw_from_lowest(p)
+        .into_iter()
+        .map(Point::new)
+        .collect::<Vec<_>>();
+    let len = points.len();
+    (0..len).all(|i| {
+        let o = Point::orient_along_direction(
+            &points[i],
+            Direction::Through(&points[(i + 1) % len]),
+            &points[(i + 2) % len],
+        );
+        o != Orientation::ClockWise
+    })
+}
+
+/// Minkowski sum of two convex polygons, by merging their sides in the order of their angles.
+/// The result is counterclockwise, and has at most as many vertices as both inputs together.
+pub fn convex_minkowski_sum(a: &Polygon<f64>, b: &Polygon<f64>) -> Polygon<f64> {
+    let a = ccw_from_lowest(a);
+    let b = ccw_from_lowest(b);
+    let side = |p: &[[f64; 2]], i: usize| {
+        let (s, d) = (p[i % p.len()], p[(i + 1) % p.len()]);
+        [d[0] - s[0], d[1] - s[1]]
+    };
+
+    let mut out: Vec<[f64; 2]> = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let pa = a[i % a.len()];
+        let pb = b[j % b.len()];
+        let p = [pa[0] + pb[0], pa[1] + pb[1]];
+        if out.last() != Some(&p) {
+            out.push(p);
+        }
+
+        // take the side turning least, or both if they are parallel
+        let turn = if i == a.len() {
+            -1.0
+        } else if j == b.len() {
+            1.0
+        } else {
+            cross(side(&a, i), side(&b, j))
+        };
+        if turn >= 0.0 {
+            i += 1;
+        }
+        if turn <= 0.0 {
+            j += 1;
+        }
+    }
+    if out.len() > 1 && out.first() == out.last() {
+        out.pop();
+    }
+    Polygon::new_unchecked(out.into_iter().map(Point::new).collect())
+}
+
+impl SimplicalChain<f64> {
+    /// Minkowski sum of two polygons. Convex polygons are summed directly, otherwise both are
+    /// decomposed into convex pieces with `convex_decomposition`, and the sums of all pairs of
+    /// pieces are merged with `union_all`. Obstacles in the configuration space of an agent are
+    /// the sums of the obstacles and the agent reflected through its reference point. Fails if a
+    /// decomposition fails.
+    pub fn minkowski_sum(
+        a: &Polygon<f64>,
+        b: &Polygon<f64>,
+    ) -> anyhow::Result<SimplicalChain<f64>> {
+        let pieces = |p: &Polygon<f64>| {
+            if is_convex(p) {
+                Ok(vec![p.clone()])
+            } else {
+                convex_decomposition(&SimplicalChain::from_polygon(p))
+            }
+        };
+        let pieces_a = pieces(a)?;
+        let pieces_b = pieces(b)?;
+
+        let sums = pieces_a.iter().flat_map(|pa| {
+            pieces_b.iter().map(move |pb| {
+                let sum = convex_minkowski_sum(pa, pb);
+                let points = sum.iter().collect::<Vec<_>>();
+                let simplices = (0..points.len())
+                    .map(|i| Simplex {
+                        src: *points[i],
+                        dst: *points[(i + 1) % points.len()],
+                    })
+                    .collect();
+                SimplicalChain { simplices }
+            })
+        });
+        Ok(SimplicalChain::union_all(sums))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn polygon(points: &[[f64; 2]]) -> Polygon<f64> {
+        Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap()
+    }
+
+    #[test]
+    fn minkowski_convex() {
+        let square = polygon(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        let triangle = polygon(&[[0.0, 0.0], [2.0, 0.0], [0.0, 2.0]]);
+
+        let sum = convex_minkowski_sum(&square, &square);
+        assert_eq!(sum.iter().count(), 4);
+        assert_eq!(SimplicalChain::from_polygon(&sum).signed_area(), 4.0);
+
+        // triangle, with the square along its sides: 2 + 1 + 2 + 2
+        let sum = convex_minkowski_sum(&triangle, &square);
+        assert_eq!(sum.iter().count(), 5);
+        assert_eq!(
+            SimplicalChain::minkowski_sum(&triangle, &square)
+                .unwrap()
+                .signed_area(),
+            7.0
+        );
+    }
+
+    #[test]
+    fn minkowski_l_shape() {
+        let l = polygon(&[
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 2.0],
+            [0.0, 2.0],
+        ]);
+        let square = polygon(&[[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]]);
+
+        // both arms grown by half a unit
+        let sum = SimplicalChain::minkowski_sum(&l, &square).unwrap();
+        assert_eq!(sum.signed_area(), 8.0);
+        assert_eq!(sum.rings().unwrap().len(), 1);
+        assert_eq!(
+            SimplicalChain::minkowski_sum(&square, &l)
+                .unwrap()
+                .signed_area(),
+            8.0
+        );
+    }
+
+    #[test]
+    fn minkowski_obstacles() {
+        use rand::prelude::*;
+
+        // configuration space of a triangular agent among rectangles
+        let mut rng = StdRng::seed_from_u64(0);
+        let rects = crate::gen_rects(&mut rng, 30.0, 20);
+        // a triangle pointing left, reflected through its tip
+        let reflected = polygon(&[[0.0, 0.0], [-1.0, -0.5], [-1.0, 0.5]]);
+        let obstacles = SimplicalChain::union_all(
+            rects
+                .iter()
+                .map(|r| SimplicalChain::minkowski_sum(&r.polygon(1), &reflected).unwrap()),
+        );
+        let rects = SimplicalChain::union_all(
+            rects
+                .iter()
+                .map(|r| SimplicalChain::from_polygon(&r.polygon(1))),
+        );
+        assert!(obstacles.polygons().is_ok());
+        assert!(obstacles.signed_area() > rects.signed_area());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::boolean::{Simplex, SimplicalChain};
+use crate::cross;
+use crate::snap::Snap;
+use rgeometry::data::*;
+
+type Result<T> = anyhow::Result<T>;
+
+/// Shape of the offset boundary around convex corners, see `SimplicalChain::offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JoinStyle {
+    /// Extend the sides until they meet. The corner is beveled if the miter is longer than the
+    /// limit times the distance.
+    Miter(f64),
+    /// Circular arc, with the number of segments for a full circle
+    Round(usize),
+    /// Connect the offset sides with a segment
+    Bevel,
+}
+
+// sine of the angle between sides, below which the sides are merged
+const COLLINEAR_SIN: f64 = 1e-9;
+
+// grid spacing for snapping, in bits below the extent of the chain
+const SNAP_BITS: i32 = 36;
+
+fn add(p: [f64; 2], v: [f64; 2], s: f64) -> [f64; 2] {
+    [p[0] + v[0] * s, p[1] + v[1] * s]
+}
+
+// unit normal on the right of `a -> b`
+fn normal(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    let [dx, dy] = [b[0] - a[0], b[1] - a[1]];
+    let len = (dx * dx + dy * dy).sqrt();
+    [dy / len, -dx / len]
+}
+
+fn chain(points: &[[f64; 2]]) -> SimplicalChain<f64> {
+    let simplices = (0..points.len())
+        .map(|i| Simplex {
+            src: Point::new(points[i]),
+            dst: Point::new(points[(i + 1) % points.len()]),
+        })
+        .collect();
+    SimplicalChain { simplices }
+}
+
+// drop vertices between nearly collinear sides, which make slivers between offset sides
+fn simplify(ring: &[Point<f64>]) -> Vec<[f64; 2]> {
+    let mut out = ring.iter().map(|p| p.array).collect::<Vec<_>>();
+    let mut i = 0;
+    while out.len() > 3 && i < out.len() {
+        let len = out.len();
+        let a = out[(i + len - 1) % len];
+        let v = out[i];
+        let b = out[(i + 1) % len];
+        let u0 = [v[0] - a[0], v[1] - a[1]];
+        let u1 = [b[0] - v[0], b[1] - v[1]];
+        let len0 = (u0[0] * u0[0] + u0[1] * u0[1]).sqrt();
+        let len1 = (u1[0] * u1[0] + u1[1] * u1[1]).sqrt();
+        let dot = u0[0] * u1[0] + u0[1] * u1[1];
+        if dot > 0.0 && cross(u0, u1).abs() <= COLLINEAR_SIN * len0 * len1 {
+            out.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+// area swept by offsetting the side to the right, and the join at the corner after the side.
+// at reflex corners, sides are trimmed where their offsets meet, so that pieces of adjacent
+// sides share an edge instead of crossing each other near the corner
+fn ring_pieces(
+    ring: &[[f64; 2]],
+    distance: f64,
+    join: JoinStyle,
+    out: &mut Vec<SimplicalChain<f64>>,
+) {
+    let len = ring.len();
+    let side = |i: usize| {
+        let a = ring[i % len];
+        let b = ring[(i + 1) % len];
+        let d = [b[0] - a[0], b[1] - a[1]];
+        (d, (d[0] * d[0] + d[1] * d[1]).sqrt(), normal(a, b))
+    };
+
+    // length trimmed from the offset of both sides at each vertex, and the meeting point
+    let mut trims = Vec::with_capacity(len);
+    for (i, v) in ring.iter().enumerate() {
+        let (d0, _, n0) = side(i + len - 1);
+        let (d1, _, n1) = side(i);
+        let dot = n0[0] * n1[0] + n0[1] * n1[1];
+        let trim = if cross(d0, d1) < 0.0 && 1.0 + dot > 0.0 {
+            let m = [n0[0] + n1[0], n0[1] + n1[1]];
+            let meet = add(*v, m, distance / (1.0 + dot));
+            Some((distance * cross(n0, n1).abs() / (1.0 + dot), meet))
+        } else {
+            None
+        };
+        trims.push(trim);
+    }
+    let trim_len = |i: usize| trims[i % len].map(|(t, _)| t).unwrap_or(0.0);
+    let allowed = (0..len)
+        .map(|i| {
+            trim_len(i) + trim_len(i + len - 1) < side(i + len - 1).1
+                && trim_len(i) + trim_len(i + 1) < side(i).1
+        })
+        .collect::<Vec<_>>();
+    for (trim, allowed) in trims.iter_mut().zip(allowed) {
+        if !allowed {
+            *trim = None;
+        }
+    }
+
+    for i in 0..len {
+        let a = ring[i];
+        let v = ring[(i + 1) % len];
+        let (d0, _, n0) = side(i);
+        let (d1, _, n1) = side(i + 1);
+
+        let start = trims[i].map(|(_, p)| p).unwrap_or(add(a, n0, distance));
+        let end = trims[(i + 1) % len]
+            .map(|(_, p)| p)
+            .unwrap_or(add(v, n0, distance));
+        out.push(chain(&[a, start, end, v]));
+
+        // the offset sides overlap at reflex corners, and meet at straight ones
+        if cross(d0, d1) <= 0.0 {
+            continue;
+        }
+        let p0 = add(v, n0, distance);
+        let p1 = add(v, n1, distance);
+        let dot = n0[0] * n1[0] + n0[1] * n1[1];
+        let corner = match join {
+            JoinStyle::Miter(limit) if 1.0 + dot > 0.0 && 2.0 / (1.0 + dot) <= limit * limit => {
+                let m = [n0[0] + n1[0], n0[1] + n1[1]];
+                vec![v, p0, add(v, m, distance / (1.0 + dot)), p1]
+            }
+            JoinStyle::Round(segments) => {
+                let angle = cross(n0, n1).atan2(dot);
+                let count = (angle / std::f64::consts::TAU * segments as f64).ceil() as usize;
+                let start = n0[1].atan2(n0[0]);
+                let mut corner = vec![v, p0];
+                for k in 1..count {
+                    let theta = start + angle * k as f64 / count as f64;
+                    corner.push(add(v, [theta.cos(), theta.sin()], distance));
+                }
+                corner.push(p1);
+                corner
+            }
+            _ => vec![v, p0, p1],
+        };
+        out.push(chain(&corner));
+    }
+}
+
+impl SimplicalChain<f64> {
+    /// Offset the boundary by `distance`, growing the area if positive and shrinking it if
+    /// negative. The result is a union of the area and the areas swept by offsetting each side
+    /// and corner, or a difference for negative distances. Vertices are snapped to a fine grid,
+    /// and vertices between nearly collinear sides are dropped first. The area and the pieces
+    /// are snap rounded together on the grid before they are merged. Fails if the chain is not
+    /// closed.
+    pub fn offset(&self, distance: f64, join: JoinStyle) -> Result<SimplicalChain<f64>> {
+        if distance == 0.0 {
+            return Ok(self.clone());
+        }
+
+        // snap to a power of two grid, so that nearly axis aligned sides become exact
+        let extent = self
+            .simplices
+            .iter()
+            .flat_map(|s| s.src.array)
+            .fold(distance.abs(), |extent, v| extent.max(v.abs()));
+        let snap = Snap::Grid(2f64.powi(extent.log2().ceil() as i32 - SNAP_BITS));
+        let snapped = self
+            .simplices
+            .iter()
+            .map(|s| Simplex {
+                src: snap.apply(&s.src),
+                dst: snap.apply(&s.dst),
+            })
+            .filter(|s| s.src != s.dst)
+            .collect();
+
+        let rings = SimplicalChain { simplices: snapped }
+            .rings()?
+            .iter()
+            .map(|ring| simplify(ring))
+            .collect::<Vec<_>>();
+        let simplices = rings
+            .iter()
+            .flat_map(|ring| chain(ring).simplices)
+            .collect();
+        let area = SimplicalChain { simplices };
+
+        // sides to offset have the grown area on the left
+        let mut pieces = Vec::new();
+        for mut ring in rings {
+            if distance < 0.0 {
+                ring.reverse();
+            }
+            ring_pieces(&ring, distance.abs(), join, &mut pieces);
+        }
+        // snap round the area and the pieces together, so that they only meet at shared vertices.
+        // offset sides cross each other near the center of an arc offset by its radius
+        pieces.insert(0, area);
+        let mut pieces = SimplicalChain::snap_round(&pieces, snap.spacing().unwrap());
+        let area = pieces.remove(0);
+        let swept = SimplicalChain::union_all(pieces);
+
+        let out = if distance > 0.0 {
+            area.union(&swept)
+        } else {
+            area.subtract(&swept)
+        };
+        Ok(out.cancel_opposite())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn square(extent: f64) -> SimplicalChain<f64> {
+        let e = extent;
+        chain(&[[-e, -e], [e, -e], [e, e], [-e, e]])
+    }
+
+    fn assert_near(v: f64, expected: f64) {
+        assert!((v - expected).abs() < 1e-9, "{} != {}", v, expected);
+    }
+
+    #[test]
+    fn offset_square() {
+        let sx = square(1.0);
+
+        let out = sx.offset(1.0, JoinStyle::Miter(2.0)).unwrap();
+        assert_near(out.signed_area(), 16.0);
+        assert_eq!(out.rings().unwrap().len(), 1);
+
+        // 2x2, with four 2x1 sides and four triangles
+        let out = sx.offset(1.0, JoinStyle::Bevel).unwrap();
+        assert_near(out.signed_area(), 4.0 + 8.0 + 2.0);
+
+        // miter of a right angle is sqrt(2) times the distance
+        let out = sx.offset(1.0, JoinStyle::Miter(1.2)).unwrap();
+        assert_near(out.signed_area(), 14.0);
+
+        let out = sx.offset(1.0, JoinStyle::Round(64)).unwrap();
+        let circle = 64.0 / 2.0 * (std::f64::consts::TAU / 64.0).sin();
+        assert_near(out.signed_area(), 4.0 + 8.0 + circle);
+    }
+
+    #[test]
+    fn offset_negative() {
+        let sx = square(2.0);
+        for join in [
+            JoinStyle::Miter(2.0),
+            JoinStyle::Round(16),
+            JoinStyle::Bevel,
+        ] {
+            let out = sx.offset(-1.0, join).unwrap();
+            assert_near(out.signed_area(), 4.0);
+            assert_eq!(out.rings().unwrap().len(), 1);
+        }
+
+        // eroded away
+        let out = sx.offset(-3.0, JoinStyle::Bevel).unwrap();
+        assert!(out.simplices.is_empty());
+    }
+
+    #[test]
+    fn offset_hole() {
+        // 8x8 frame around a 4x4 hole, the hole shrinks to 2x2
+        let sx = square(4.0).subtract(&square(2.0));
+        let out = sx.offset(1.0, JoinStyle::Miter(2.0)).unwrap();
+        assert_near(out.signed_area(), 100.0 - 4.0);
+
+        let polys = out.polygons().unwrap();
+        assert_eq!(polys.len(), 1);
+        assert_eq!(polys[0].holes.len(), 1);
+
+        // the result feeds back into the boolean ops
+        let out = out.union(&square(1.0));
+        assert_near(out.signed_area(), 100.0);
+    }
+
+    #[test]
+    fn offset_round_back() {
+        // inward offsets of the sides of an arc cross each other near its center
+        let sx = square(1.0);
+        for segments in [8, 32] {
+            let grown = sx.offset(0.5, JoinStyle::Round(segments)).unwrap();
+            let restored = grown.offset(-0.5, JoinStyle::Miter(2.0)).unwrap();
+            assert_eq!(restored.rings().unwrap().len(), 1);
+            assert!((restored.signed_area() - 4.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn offset_rects() {
+        use rand::prelude::*;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let rects = crate::gen_rects(&mut rng, 30.0, 30);
+        let sx = SimplicalChain::union_all(
+            rects
+                .iter()
+                .map(|r| SimplicalChain::from_polygon(&r.polygon(1))),
+        );
+
+        let grown = sx.offset(0.5, JoinStyle::Round(16)).unwrap();
+        let shrunk = sx.offset(-0.3, JoinStyle::Miter(2.0)).unwrap();
+        assert!(grown.polygons().is_ok());
+        assert!(shrunk.polygons().is_ok());
+        assert!(shrunk.signed_area() < sx.signed_area() && sx.signed_area() < grown.signed_area());
+
+        // no gaps between the rectangles are narrower than twice the distance, so shrinking
+        // back restores the area. mitered corners come back exactly, and round ones almost
+        let d = 0.1;
+        for (join, tolerance) in [(JoinStyle::Miter(2.0), 1e-6), (JoinStyle::Round(16), 1e-3)] {
+            let grown = sx.offset(d, join).unwrap();
+            let restored = grown.offset(-d, JoinStyle::Miter(2.0)).unwrap();
+            assert_eq!(
+                restored.polygons().unwrap().len(),
+                sx.polygons().unwrap().len()
+            );
+            assert!((restored.signed_area() - sx.signed_area()).abs() < tolerance);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::delaunay::*;
+use rgeometry::{data::*, PolygonScalar};
+
+/// Cell of the power diagram, the region where `site` has the smallest power distance
+#[derive(Debug, Clone)]
+pub struct PowerCell<T: PolygonScalar> {
+    pub site: VertIdx,
+    /// power centers of the triangles around `site`, in counterclockwise order
+    pub vertices: Vec<Point<T>>,
+    /// false if `site` is on the convex hull. `vertices` is an open chain then, and the cell
+    /// extends to the infinity from its both ends
+    pub bounded: bool,
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    /// Power center of the triangle, the point with the same power distance to its weighted
+    /// vertices. Returns the center and the power distance, which is the squared radius of the
+    /// orthogonal circle. Same as `circumcircle` if all weights are zero.
+    pub fn power_center(&self, tri: TriIdx) -> (Point<T>, T) {
+        let t = self.tri(tri);
+
+        let [v0, v1, v2] = t.vertices;
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        let p2 = self.vert(v2);
+        let w0 = self.weight(v0);
+
+        let bx = p1.array[0].clone() - p0.array[0].clone();
+        let by = p1.array[1].clone() - p0.array[1].clone();
+        let cx = p2.array[0].clone() - p0.array[0].clone();
+        let cy = p2.array[1].clone() - p0.array[1].clone();
+
+        let b_sq = bx.clone() * bx.clone() + by.clone() * by.clone() - self.weight(v1) + w0.clone();
+        let c_sq = cx.clone() * cx.clone() + cy.clone() * cy.clone() - self.weight(v2) + w0.clone();
+        let d = T::from_constant(2) * (bx.clone() * cy.clone() - by.clone() * cx.clone());
+
+        let ux = (cy * b_sq.clone() - by * c_sq.clone()) / d.clone();
+        let uy = (bx * c_sq - cx * b_sq) / d;
+
+        let power = ux.clone() * ux.clone() + uy.clone() * uy.clone() - w0;
+        let center = Point::new([p0.array[0].clone() + ux, p0.array[1].clone() + uy]);
+        (center, power)
+    }
+
+    /// Power diagram, the dual of the regular triangulation built with `insert_weighted`. Returns
+    /// a cell for each non-super vertex which is used by triangles, in the order of `VertIdx`.
+    pub fn power_diagram(&self) -> Vec<PowerCell<T>> {
+        // a triangle around each vertex
+        let mut first = vec![None; self.vertices.len()];
+        for (idx, t) in self.triangles() {
+            for (i, v) in t.vertices.iter().enumerate() {
+                if first[v.0].is_none() {
+                    first[v.0] = Some((idx, SubIdx(i)));
+                }
+            }
+        }
+
+        let mut cells = Vec::new();
+        for (v, first) in first.into_iter().enumerate() {
+            let site = VertIdx(v);
+            let (mut tri, mut sub) = match first {
+                Some(first) if !site.is_super() => first,
+                _ => continue,
+            };
+
+            // triangles around the site, in counterclockwise order
+            let mut ring = Vec::new();
+            loop {
+                ring.push(tri);
+                let n = match self.tri(tri).neighbors[sub.0] {
+                    Some(n) => n,
+                    None => break,
+                };
+                if n == ring[0] {
+                    break;
+                }
+                let t = self.tri(n);
+                sub = SubIdx(t.vertices.iter().position(|u| *u == site).unwrap());
+                tri = n;
+            }
+
+            // start after super triangles, so the chain of an unbounded cell is not split
+            let bounded = ring.iter().all(|t| !self.tri(*t).is_super());
+            if let Some(last) = ring.iter().rposition(|t| self.tri(*t).is_super()) {
+                ring.rotate_left(last + 1);
+            }
+
+            let vertices = ring
+                .into_iter()
+                .filter(|t| !self.tri(*t).is_super())
+                .map(|t| self.power_center(t).0)
+                .collect();
+            cells.push(PowerCell {
+                site,
+                vertices,
+                bounded,
+            });
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn power_center() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        for p in [[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]] {
+            net.insert_weighted(&Point::new(p), 1.0, &mut r).unwrap();
+        }
+
+        let idx = (0..net.tri_len())
+            .map(TriIdx)
+            .find(|t| !net.tri(*t).is_super())
+            .unwrap();
+        let (center, power) = net.power_center(idx);
+        assert_eq!(center, Point::new([2.0, 2.0]));
+        assert_eq!(power, 7.0);
+    }
+
+    #[test]
+    fn power_diagram_square() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        for p in [[-2.0, -2.0], [2.0, -2.0], [2.0, 2.0], [-2.0, 2.0]] {
+            net.insert_weighted(&Point::new(p), 0.0, &mut r).unwrap();
+        }
+        // the heavy center pushes the bisectors towards the corners, from |x| + |y| = 2
+        let center = net
+            .insert_weighted(&Point::new([0.0, 0.0]), 4.0, &mut r)
+            .unwrap()
+            .unwrap();
+
+        let cells = net.power_diagram();
+        assert_eq!(cells.len(), 5);
+
+        let cell = cells.iter().find(|c| c.site == center).unwrap();
+        assert!(cell.bounded);
+        assert_eq!(cell.vertices.len(), 4);
+        for p in &cell.vertices {
+            assert_eq!(p.array[0].abs() + p.array[1].abs(), 3.0);
+            assert_eq!(p.array[0] * p.array[1], 0.0);
+        }
+
+        for cell in cells.iter().filter(|c| c.site != center) {
+            assert!(!cell.bounded);
+            assert_eq!(cell.vertices.len(), 2);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::delaunay::*;
+use rgeometry::{data::*, PolygonScalar};
+
+// true if `c` is strictly inside of the circle with diameter `a`-`b`
+fn inside_diametral<T: PolygonScalar>(a: &Point<T>, b: &Point<T>, c: &Point<T>) -> bool {
+    let dot = (a.array[0].clone() - c.array[0].clone()) * (b.array[0].clone() - c.array[0].clone())
+        + (a.array[1].clone() - c.array[1].clone()) * (b.array[1].clone() - c.array[1].clone());
+    dot < T::from_constant(0)
+}
+
+pub(crate) struct DisjointSet {
+    parents: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+
+    pub(crate) fn find(&mut self, mut idx: usize) -> usize {
+        while self.parents[idx] != idx {
+            self.parents[idx] = self.parents[self.parents[idx]];
+            idx = self.parents[idx];
+        }
+        idx
+    }
+
+    pub(crate) fn union(&mut self, idx0: usize, idx1: usize) -> bool {
+        let root0 = self.find(idx0);
+        let root1 = self.find(idx1);
+        if root0 == root1 {
+            return false;
+        }
+        self.parents[root1] = root0;
+        true
+    }
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    // neighbor vertices of each vertex, indexed by `VertIdx`
+    fn vert_neighbors(&self, edges: &[(VertIdx, VertIdx)]) -> Vec<Vec<VertIdx>> {
+        let mut neighbors = vec![Vec::new(); self.vertices.len()];
+        for (v0, v1) in edges {
+            neighbors[v0.0].push(*v1);
+            neighbors[v1.0].push(*v0);
+        }
+        neighbors
+    }
+
+    /// Euclidean minimum spanning tree of non-super vertices, in order of increasing length.
+    /// Edges are taken from the network, so the result is exact only if the network is not
+    /// constrained.
+    pub fn emst(&self) -> Vec<(VertIdx, VertIdx)> {
+        let mut edges = self
+            .edges()
+            .into_iter()
+            .map(|(v0, v1)| (dist_sq(self.vert(v0), self.vert(v1)), v0, v1))
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut set = DisjointSet::new(self.vertices.len());
+        let mut out = Vec::new();
+        for (_, v0, v1) in edges {
+            if set.union(v0.0, v1.0) {
+                out.push((v0, v1));
+            }
+        }
+        out
+    }
+
+    /// Gabriel graph of non-super vertices. An edge is in the graph if no other vertex lies in the
+    /// circle with the edge as a diameter.
+    pub fn gabriel_graph(&self) -> Vec<(VertIdx, VertIdx)> {
+        let edges = self.edges();
+        let neighbors = self.vert_neighbors(&edges);
+        edges
+            .into_iter()
+            .filter(|(v0, v1)| self.is_gabriel(&neighbors, *v0, *v1))
+            .collect()
+    }
+
+    // it is enough to check common neighbors, which include opposite vertices of the edge
+    fn is_gabriel(&self, neighbors: &[Vec<VertIdx>], v0: VertIdx, v1: VertIdx) -> bool {
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        neighbors[v0.0]
+            .iter()
+            .filter(|v| neighbors[v1.0].contains(v))
+            .all(|v| !inside_diametral(p0, p1, self.vert(*v)))
+    }
+
+    /// Relative neighborhood graph of non-super vertices. An edge is in the graph if no other
+    /// vertex is closer to both of its endpoints than they are to each other. Candidates are
+    /// edges of the Gabriel graph, and neighbors of their endpoints are checked.
+    pub fn relative_neighborhood_graph(&self) -> Vec<(VertIdx, VertIdx)> {
+        let edges = self.edges();
+        let neighbors = self.vert_neighbors(&edges);
+        edges
+            .into_iter()
+            .filter(|(v0, v1)| {
+                if !self.is_gabriel(&neighbors, *v0, *v1) {
+                    return false;
+                }
+
+                let p0 = self.vert(*v0);
+                let p1 = self.vert(*v1);
+                let d = dist_sq(p0, p1);
+                neighbors[v0.0]
+                    .iter()
+                    .chain(neighbors[v1.0].iter())
+                    .filter(|v| *v != v0 && *v != v1)
+                    .all(|v| {
+                        let p = self.vert(*v);
+                        !(dist_sq(p0, p) < d && dist_sq(p1, p) < d)
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn proximity_acute() {
+        let net = test_net(&[[0.0, 0.0], [4.0, 0.0], [2.0, 3.0]].map(Point::new));
+        let (a, b, c) = (VertIdx(3), VertIdx(4), VertIdx(5));
+
+        assert_eq!(net.edges(), vec![(a, b), (a, c), (b, c)]);
+        assert_eq!(net.gabriel_graph(), vec![(a, b), (a, c), (b, c)]);
+        assert_eq!(net.relative_neighborhood_graph(), vec![(a, c), (b, c)]);
+
+        let mut emst = net.emst();
+        emst.sort();
+        assert_eq!(emst, vec![(a, c), (b, c)]);
+    }
+
+    #[test]
+    fn proximity_obtuse() {
+        let net = test_net(&[[0.0, 0.0], [4.0, 0.0], [2.0, 1.0], [2.5, -5.0]].map(Point::new));
+        let (a, b, c, d) = (VertIdx(3), VertIdx(4), VertIdx(5), VertIdx(6));
+
+        assert_eq!(net.edges(), vec![(a, b), (a, c), (a, d), (b, c), (b, d)]);
+        assert_eq!(net.gabriel_graph(), vec![(a, c), (a, d), (b, c), (b, d)]);
+        assert_eq!(
+            net.relative_neighborhood_graph(),
+            vec![(a, c), (b, c), (b, d)]
+        );
+        assert_eq!(net.emst(), vec![(a, c), (b, c), (b, d)]);
+    }
+}
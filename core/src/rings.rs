@@ -0,0 +1,370 @@
+use crate::area_2x;
+use crate::boolean::SimplicalChain;
+use rgeometry::{data::*, Orientation, PolygonScalar};
+
+type Result<T> = anyhow::Result<T>;
+
+/// Polygon with holes, reconstructed from `SimplicalChain::polygons`
+#[derive(Debug, Clone)]
+pub struct PolygonWithHoles<T> {
+    /// counterclockwise outer boundary
+    pub outer: Polygon<T>,
+    /// clockwise holes
+    pub holes: Vec<Polygon<T>>,
+    /// index of the polygon with the hole containing this polygon
+    pub parent: Option<usize>,
+}
+
+// twice the signed area of the ring, positive if counterclockwise
+fn signed_area_2x<T: PolygonScalar>(ring: &[Point<T>]) -> T {
+    area_2x(ring.iter().zip(ring.iter().cycle().skip(1)))
+}
+
+fn abs<T: PolygonScalar>(v: T) -> T {
+    if v < T::from_constant(0) {
+        T::from_constant(0) - v
+    } else {
+        v
+    }
+}
+
+pub(crate) fn on_segment<T: PolygonScalar>(a: &Point<T>, b: &Point<T>, p: &Point<T>) -> bool {
+    let between = |i: usize| {
+        let (lo, hi) = if a.array[i] < b.array[i] {
+            (&a.array[i], &b.array[i])
+        } else {
+            (&b.array[i], &a.array[i])
+        };
+        *lo <= p.array[i] && p.array[i] <= *hi
+    };
+    Point::orient_along_direction(a, Direction::Through(b), p) == Orientation::CoLinear
+        && between(0)
+        && between(1)
+}
+
+// contribution of `a -> b` to the winding number around `p`: 1 if it crosses the ray to the
+// right of `p` upwards, -1 if downwards
+pub(crate) fn crossing<T: PolygonScalar>(a: &Point<T>, b: &Point<T>, p: &Point<T>) -> i32 {
+    use Orientation::*;
+
+    let o = Point::orient_along_direction(a, Direction::Through(b), p);
+    if a.array[1] <= p.array[1] {
+        if b.array[1] > p.array[1] && o == CounterClockWise {
+            return 1;
+        }
+    } else if b.array[1] <= p.array[1] && o == ClockWise {
+        return -1;
+    }
+    0
+}
+
+// winding number of the ring around `p`, or `None` if `p` is on the ring
+fn ring_winding<T: PolygonScalar>(ring: &[Point<T>], p: &Point<T>) -> Option<i32> {
+    let mut winding = 0;
+    for (i, a) in ring.iter().enumerate() {
+        let b = &ring[(i + 1) % ring.len()];
+        if on_segment(a, b, p) {
+            return None;
+        }
+        winding += crossing(a, b, p);
+    }
+    Some(winding)
+}
+
+// `inner` does not cross `outer`, so a point of `inner` which is not on `outer` decides
+fn ring_contains<T: PolygonScalar>(outer: &[Point<T>], inner: &[Point<T>]) -> bool {
+    for p in inner {
+        if let Some(winding) = ring_winding(outer, p) {
+            return winding != 0;
+        }
+    }
+
+    // all vertices are on `outer`, try midpoints of sides
+    for (i, p) in inner.iter().enumerate() {
+        let q = &inner[(i + 1) % inner.len()];
+        let mid = Point::new([
+            (p.array[0].clone() + q.array[0].clone()) / T::from_constant(2),
+            (p.array[1].clone() + q.array[1].clone()) / T::from_constant(2),
+        ]);
+        if let Some(winding) = ring_winding(outer, &mid) {
+            return winding != 0;
+        }
+    }
+    false
+}
+
+fn dot<T: PolygonScalar>(v: &Point<T>, u: &Point<T>, w: &Point<T>) -> T {
+    (u.array[0].clone() - v.array[0].clone()) * (w.array[0].clone() - v.array[0].clone())
+        + (u.array[1].clone() - v.array[1].clone()) * (w.array[1].clone() - v.array[1].clone())
+}
+
+// `w` comes before `w1` in the clockwise sweep around `v`, starting from the direction to `u`
+fn cw_before<T: PolygonScalar>(v: &Point<T>, u: &Point<T>, w: &Point<T>, w1: &Point<T>) -> bool {
+    use Orientation::*;
+
+    // quarter of the sweep: right of `v -> u`, opposite to `u`, left of `v -> u`, towards `u`
+    let half = |w: &Point<T>| match Point::orient_along_direction(v, Direction::Through(u), w) {
+        ClockWise => 0,
+        CounterClockWise => 2,
+        CoLinear if dot(v, u, w) < T::from_constant(0) => 1,
+        CoLinear => 3,
+    };
+    let (h, h1) = (half(w), half(w1));
+    if h != h1 {
+        return h < h1;
+    }
+    Point::orient_along_direction(v, Direction::Through(w), w1) == ClockWise
+}
+
+impl<T: PolygonScalar + Clone> SimplicalChain<T> {
+    /// Stitch simplices into closed rings, with the interior on the left. At a vertex shared by
+    /// several rings, the walk takes the sharpest left turn, so rings touching at a vertex are
+    /// separated. Simplices of zero length are skipped. Fails if a simplex can not be closed into a ring, if a ring has zero area, or
+    /// if the walk has to turn back along the simplex it came from, as at a spike or a pair of
+    /// opposite simplices.
+    pub fn rings(&self) -> Result<Vec<Vec<Point<T>>>> {
+        let simplices = &self.simplices;
+
+        // simplices sorted by their source point
+        let mut by_src = (0..simplices.len()).collect::<Vec<_>>();
+        by_src.sort_by(|a, b| {
+            simplices[*a]
+                .src
+                .array
+                .partial_cmp(&simplices[*b].src.array)
+                .unwrap()
+        });
+        let outgoing = |p: &Point<T>| {
+            let start = by_src.partition_point(|i| simplices[*i].src.array < p.array);
+            let end = by_src.partition_point(|i| simplices[*i].src.array <= p.array);
+            &by_src[start..end]
+        };
+
+        // simplices of zero length, as from rounding, do not bound anything
+        let mut used = simplices.iter().map(|s| s.src == s.dst).collect::<Vec<_>>();
+        let mut rings = Vec::new();
+        for first in 0..simplices.len() {
+            if used[first] {
+                continue;
+            }
+            used[first] = true;
+
+            let start = &simplices[first].src;
+            let mut ring = vec![start.clone()];
+            let mut cur = first;
+            loop {
+                let s = &simplices[cur];
+                if s.dst == *start {
+                    break;
+                }
+                ring.push(s.dst.clone());
+
+                let mut next: Option<usize> = None;
+                for i in outgoing(&s.dst) {
+                    if used[*i] {
+                        continue;
+                    }
+                    let better = match next {
+                        Some(n) => cw_before(&s.dst, &s.src, &simplices[*i].dst, &simplices[n].dst),
+                        None => true,
+                    };
+                    if better {
+                        next = Some(*i);
+                    }
+                }
+                cur = match next {
+                    Some(n) => n,
+                    None => anyhow::bail!("rings: dangling edge, {:?} -> {:?}", s.src, s.dst),
+                };
+                used[cur] = true;
+
+                // the sharpest left turn goes back only if nothing else is left
+                let w = &simplices[cur].dst;
+                if Point::orient_along_direction(&s.src, Direction::Through(&s.dst), w)
+                    == Orientation::CoLinear
+                    && dot(&s.dst, &s.src, w) > T::from_constant(0)
+                {
+                    anyhow::bail!("rings: spike at {:?}", s.dst);
+                }
+            }
+
+            if signed_area_2x(&ring) == T::from_constant(0) {
+                anyhow::bail!("rings: ring with zero area, {:?}", ring[0]);
+            }
+            rings.push(ring);
+        }
+        Ok(rings)
+    }
+
+    /// Polygons with holes, from rings of `rings`. Counterclockwise rings are outer boundaries,
+    /// and each clockwise ring is a hole of the smallest outer boundary containing it. Polygons
+    /// inside of a hole keep the index of the polygon with the hole as `parent`. Fails on
+    /// dangling edges, or holes outside of any outer boundary.
+    pub fn polygons(&self) -> Result<Vec<PolygonWithHoles<T>>> {
+        let zero = T::from_constant(0);
+        let rings = self
+            .rings()?
+            .into_iter()
+            .map(|ring| (signed_area_2x(&ring), ring))
+            .collect::<Vec<_>>();
+
+        let (outers, holes): (Vec<_>, Vec<_>) = rings.into_iter().partition(|(a, _)| *a > zero);
+
+        // smallest ring of `candidates` containing `ring`
+        let smallest = |candidates: &[(T, Vec<Point<T>>)], ring: &[Point<T>]| {
+            let mut found: Option<usize> = None;
+            for (i, (area, candidate)) in candidates.iter().enumerate() {
+                if !ring_contains(candidate, ring) {
+                    continue;
+                }
+                let smaller = match found {
+                    Some(f) => abs(area.clone()) < abs(candidates[f].0.clone()),
+                    None => true,
+                };
+                if smaller {
+                    found = Some(i);
+                }
+            }
+            found
+        };
+
+        let mut polygons = outers
+            .iter()
+            .map(|(_, ring)| PolygonWithHoles {
+                outer: Polygon::new_unchecked(ring.clone()),
+                holes: Vec::new(),
+                parent: None,
+            })
+            .collect::<Vec<_>>();
+
+        // polygon of each hole
+        let mut hole_owner = Vec::with_capacity(holes.len());
+        for (_, ring) in &holes {
+            let owner = match smallest(&outers, ring) {
+                Some(owner) => owner,
+                None => anyhow::bail!("rings: hole outside of any outer boundary, {:?}", ring[0]),
+            };
+            polygons[owner]
+                .holes
+                .push(Polygon::new_unchecked(ring.clone()));
+            hole_owner.push(owner);
+        }
+
+        for (idx, (_, ring)) in outers.iter().enumerate() {
+            let hole = match smallest(&holes, ring) {
+                Some(hole) => hole,
+                None => continue,
+            };
+            // the hole is inside of the polygon itself, if the polygon is larger
+            let owner = hole_owner[hole];
+            if owner != idx && ring_contains(&outers[owner].1, ring) {
+                polygons[idx].parent = Some(owner);
+            }
+        }
+        Ok(polygons)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::boolean::Simplex;
+
+    fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> SimplicalChain<f64> {
+        let points = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+        let p = Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap();
+        SimplicalChain::from_polygon(&p)
+    }
+
+    fn area(p: &Polygon<f64>) -> f64 {
+        signed_area_2x(&p.iter().cloned().collect::<Vec<_>>()) / 2.0
+    }
+
+    #[test]
+    fn rings_touching() {
+        // two squares touching at a corner
+        let sx = square(0.0, 0.0, 1.0, 1.0).union(&square(1.0, 1.0, 2.0, 2.0));
+        let rings = sx.rings().unwrap();
+        assert_eq!(rings.len(), 2);
+        assert!(rings.iter().all(|r| r.len() == 4));
+
+        let polys = sx.polygons().unwrap();
+        assert_eq!(polys.len(), 2);
+        assert!(polys
+            .iter()
+            .all(|p| p.holes.is_empty() && p.parent.is_none()));
+    }
+
+    #[test]
+    fn polygons_nested() {
+        // a frame, with an island inside of the hole
+        let frame = square(-3.0, -3.0, 3.0, 3.0).subtract(&square(-2.0, -2.0, 2.0, 2.0));
+        let island = square(-1.0, -1.0, 1.0, 1.0);
+        let sx = frame.union(&island);
+
+        let polys = sx.polygons().unwrap();
+        assert_eq!(polys.len(), 2);
+        let (outer, inner) = if area(&polys[0].outer) > area(&polys[1].outer) {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        assert_eq!(area(&polys[outer].outer), 36.0);
+        assert_eq!(polys[outer].holes.len(), 1);
+        assert_eq!(area(&polys[outer].holes[0]), -16.0);
+        assert_eq!(polys[outer].parent, None);
+
+        assert_eq!(area(&polys[inner].outer), 4.0);
+        assert!(polys[inner].holes.is_empty());
+        assert_eq!(polys[inner].parent, Some(outer));
+    }
+
+    #[test]
+    fn rings_dangling() {
+        let mut sx = square(0.0, 0.0, 1.0, 1.0);
+        sx.simplices.pop();
+        assert!(sx.rings().is_err());
+
+        let mut sx = square(0.0, 0.0, 1.0, 1.0);
+        sx.simplices.push(Simplex {
+            src: Point::new([1.0, 1.0]),
+            dst: Point::new([5.0, 5.0]),
+        });
+        assert!(sx.polygons().is_err());
+
+        // a hole without an outer boundary
+        let mut sx = square(0.0, 0.0, 1.0, 1.0);
+        for s in &mut sx.simplices {
+            std::mem::swap(&mut s.src, &mut s.dst);
+        }
+        assert!(sx.polygons().is_err());
+    }
+
+    #[test]
+    fn rings_spike() {
+        // a spike out of a corner of the square
+        let mut sx = square(0.0, 0.0, 1.0, 1.0);
+        sx.simplices.push(Simplex {
+            src: Point::new([1.0, 1.0]),
+            dst: Point::new([2.0, 2.0]),
+        });
+        sx.simplices.push(Simplex {
+            src: Point::new([2.0, 2.0]),
+            dst: Point::new([1.0, 1.0]),
+        });
+        assert!(sx.rings().is_err());
+        assert!(sx.polygons().is_err());
+
+        // a pair of opposite simplices alone
+        let (p, q) = (Point::new([0.0, 0.0]), Point::new([1.0, 0.0]));
+        let sx = SimplicalChain {
+            simplices: vec![Simplex { src: p, dst: q }, Simplex { src: q, dst: p }],
+        };
+        assert!(sx.rings().is_err());
+
+        // a simplex of zero length, as left by rounding, is not a spike
+        let mut sx = square(0.0, 0.0, 1.0, 1.0);
+        sx.simplices.push(Simplex { src: p, dst: p });
+        assert_eq!(sx.rings().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::delaunay::*;
+use rgeometry::{data::*, PolygonScalar};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Narrow passage between two rooms
+#[derive(Debug, Clone)]
+pub struct Portal<T: PolygonScalar> {
+    /// indices into `RoomGraph::rooms`
+    pub rooms: (usize, usize),
+    pub segment: (Point<T>, Point<T>),
+}
+
+/// Partition of the free space into rooms, separated by chokepoints
+#[derive(Debug, Clone)]
+pub struct RoomGraph<T: PolygonScalar> {
+    /// triangles of each room
+    pub rooms: Vec<Vec<TriIdx>>,
+    /// room of each triangle, indexed by `TriIdx`. `None` if the triangle is not in the free space
+    pub room_of: Vec<Option<usize>>,
+    pub portals: Vec<Portal<T>>,
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    // the same side, as seen from the triangle across it
+    fn side_across(&self, idx: TriIdx, sub: SubIdx) -> Option<(TriIdx, SubIdx)> {
+        let e = self.edge_duel(&Edge::new(idx, sub))?;
+        Some((e.tri(), e.sub()))
+    }
+
+    fn side_key(&self, idx: TriIdx, sub: SubIdx) -> (VertIdx, VertIdx) {
+        let t = self.tri(idx);
+        let v_from = t.vert(sub.cw());
+        let v_to = t.vert(sub);
+        (v_from.min(v_to), v_from.max(v_to))
+    }
+
+    fn side_width_sq(&self, idx: TriIdx, sub: SubIdx) -> T {
+        let t = self.tri(idx);
+        dist_sq(self.vert(t.vert(sub.cw())), self.vert(t.vert(sub)))
+    }
+
+    /// Decompose the free space of the constrained network into rooms. `edges` are sorted
+    /// constraints, as returned by `build_net`. Triangles with three open sides are junctions,
+    /// triangles with one open side are dead ends, and triangles with two open sides form
+    /// passages between them. The narrowest side of a passage with a junction on at least one
+    /// end is a chokepoint if it is narrower than `max_width`, so dead-end corridors become
+    /// rooms of their own.
+    /// Rooms are connected components of the free space, separated by chokepoints.
+    pub fn rooms(&self, edges: &[(VertIdx, VertIdx)], max_width: T) -> RoomGraph<T> {
+        let max_width_sq = max_width.clone() * max_width;
+
+        let free = self.free_space(edges);
+        let open = (0..self.tri_len())
+            .map(|idx| {
+                if free[idx] {
+                    [0, 1, 2].map(|i| self.side_open(edges, &free, TriIdx(idx), SubIdx(i)))
+                } else {
+                    [false; 3]
+                }
+            })
+            .collect::<Vec<_>>();
+        let open_count = |idx: TriIdx| open[idx.0].iter().filter(|o| **o).count();
+
+        let mut visited = BTreeSet::new();
+        let mut chokepoints = BTreeMap::new();
+        for idx in (0..self.tri_len()).map(TriIdx) {
+            for sub in (0..3).map(SubIdx) {
+                if !open[idx.0][sub.0] || !visited.insert(self.side_key(idx, sub)) {
+                    continue;
+                }
+
+                let across = match self.side_across(idx, sub) {
+                    Some(across) => across,
+                    None => continue,
+                };
+
+                // walk the passage in both directions, until non-passage triangles
+                let mut passage = vec![(idx, sub)];
+                let mut junctions = 0;
+                let mut around_obstacle = false;
+                for (mut tri, mut tri_sub) in [(idx, sub), across] {
+                    loop {
+                        match open_count(tri) {
+                            2 => {}
+                            3 => {
+                                junctions += 1;
+                                break;
+                            }
+                            // dead end
+                            _ => break,
+                        }
+
+                        let next = (0..3)
+                            .map(SubIdx)
+                            .find(|s| *s != tri_sub && open[tri.0][s.0])
+                            .unwrap();
+                        // passage around an obstacle, without junctions
+                        if !visited.insert(self.side_key(tri, next)) {
+                            around_obstacle = true;
+                            break;
+                        }
+                        passage.push((tri, next));
+
+                        match self.side_across(tri, next) {
+                            Some((n, n_sub)) => {
+                                tri = n;
+                                tri_sub = n_sub;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+                if around_obstacle || junctions == 0 {
+                    continue;
+                }
+
+                let (tri, sub) = passage
+                    .into_iter()
+                    .map(|(tri, sub)| (self.side_width_sq(tri, sub), tri, sub))
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                    .map(|(_, tri, sub)| (tri, sub))
+                    .unwrap();
+                if self.side_width_sq(tri, sub) < max_width_sq {
+                    chokepoints.insert(self.side_key(tri, sub), (tri, sub));
+                }
+            }
+        }
+
+        // flood the free space, without crossing chokepoints
+        let mut rooms = Vec::new();
+        let mut room_of = vec![None; self.tri_len()];
+        for start in (0..self.tri_len()).map(TriIdx) {
+            if !free[start.0] || room_of[start.0].is_some() {
+                continue;
+            }
+
+            let room = rooms.len();
+            let mut tris = Vec::new();
+            let mut queue = vec![start];
+            room_of[start.0] = Some(room);
+            while let Some(idx) = queue.pop() {
+                tris.push(idx);
+                for sub in (0..3).map(SubIdx) {
+                    if !open[idx.0][sub.0] || chokepoints.contains_key(&self.side_key(idx, sub)) {
+                        continue;
+                    }
+                    if let Some((n, _)) = self.side_across(idx, sub) {
+                        if room_of[n.0].is_none() {
+                            room_of[n.0] = Some(room);
+                            queue.push(n);
+                        }
+                    }
+                }
+            }
+            tris.sort();
+            rooms.push(tris);
+        }
+
+        let mut portals = Vec::new();
+        for (tri, sub) in chokepoints.into_values() {
+            let (n, _) = match self.side_across(tri, sub) {
+                Some(across) => across,
+                None => continue,
+            };
+            let (room0, room1) = match (room_of[tri.0], room_of[n.0]) {
+                (Some(room0), Some(room1)) if room0 != room1 => (room0, room1),
+                _ => continue,
+            };
+
+            let t = self.tri(tri);
+            portals.push(Portal {
+                rooms: (room0, room1),
+                segment: (
+                    self.vert(t.vert(sub.cw())).clone(),
+                    self.vert(t.vert(sub)).clone(),
+                ),
+            });
+        }
+
+        RoomGraph {
+            rooms,
+            room_of,
+            portals,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::boolean::SimplicalChain;
+    use crate::{build_net, Rect};
+
+    #[test]
+    fn rooms_doorway() {
+        // a wall along x = 0 with a doorway at -1 < y < 1, and pillars which extend the hull
+        let rects = [
+            Rect::new(0.5, 4.0).pos(0.0, -5.0),
+            Rect::new(0.5, 4.0).pos(0.0, 5.0),
+            Rect::new(0.5, 0.5).pos(-10.0, 0.0),
+            Rect::new(0.5, 0.5).pos(10.0, 0.0),
+        ];
+        let mut sx = SimplicalChain::default();
+        for r in rects {
+            sx = sx.union(&SimplicalChain::from_polygon(&r.polygon(1)));
+        }
+        let (net, c) = build_net(20.0, &sx, true);
+
+        let graph = net.rooms(&c, 3.0);
+        assert_eq!(graph.rooms.len(), 2);
+        assert_eq!(graph.portals.len(), 1);
+
+        let (p0, p1) = &graph.portals[0].segment;
+        assert_eq!(p0.array[0].abs(), 0.5);
+        assert_eq!(p1.array[0], p0.array[0]);
+        assert_eq!(p0.array[1].abs(), 1.0);
+        assert_eq!(p1.array[1], -p0.array[1]);
+
+        let narrow = net.rooms(&c, 1.0);
+        assert_eq!(narrow.rooms.len(), 1);
+        assert!(narrow.portals.is_empty());
+    }
+
+    #[test]
+    fn rooms_dead_end() {
+        // an alley 0 < x < 8, -1 < y < 1, closed on the right, and pillars which extend the hull
+        let rects = [
+            Rect::new(4.0, 0.5).pos(4.0, 1.5),
+            Rect::new(4.0, 0.5).pos(4.0, -1.5),
+            Rect::new(0.5, 2.0).pos(8.5, 0.0),
+            Rect::new(0.5, 0.5).pos(-10.0, 0.0),
+            Rect::new(0.5, 0.5).pos(0.0, 10.0),
+            Rect::new(0.5, 0.5).pos(0.0, -10.0),
+        ];
+        let mut sx = SimplicalChain::default();
+        for r in rects {
+            sx = sx.union(&SimplicalChain::from_polygon(&r.polygon(1)));
+        }
+        let (net, c) = build_net(20.0, &sx, true);
+
+        let graph = net.rooms(&c, 3.0);
+        assert_eq!(graph.rooms.len(), 2);
+        assert_eq!(graph.portals.len(), 1);
+
+        // the alley is a room of its own
+        let alley = net
+            .triangles()
+            .find(|(_, t)| {
+                let c = t.vertices.map(|v| net.vert(v).array);
+                let x = (c[0][0] + c[1][0] + c[2][0]) / 3.0;
+                let y = (c[0][1] + c[1][1] + c[2][1]) / 3.0;
+                x > 0.0 && x < 8.0 && y.abs() < 1.0
+            })
+            .map(|(idx, _)| graph.room_of[idx.0])
+            .unwrap();
+        let (room0, room1) = graph.portals[0].rooms;
+        assert!(alley == Some(room0) || alley == Some(room1));
+
+        let narrow = net.rooms(&c, 1.0);
+        assert_eq!(narrow.rooms.len(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::boolean::{Simplex, SimplicalChain};
+use crate::delaunay::*;
+use rgeometry::PolygonScalar;
+use std::collections::BinaryHeap;
+
+// boundary edge of the remaining region, ordered by its length
+struct BoundaryEdge<T> {
+    len: T,
+    tri: TriIdx,
+    sub: SubIdx,
+}
+
+impl<T: PolygonScalar> PartialEq for BoundaryEdge<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<T: PolygonScalar> Eq for BoundaryEdge<T> {}
+
+impl<T: PolygonScalar> PartialOrd for BoundaryEdge<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PolygonScalar> Ord for BoundaryEdge<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.len
+            .partial_cmp(&other.len)
+            .unwrap()
+            .then(other.tri.cmp(&self.tri))
+            .then(other.sub.cmp(&self.sub))
+    }
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    /// Boundary of the region covered by triangles with `mask[idx]` set, as a chain with the
+    /// region on the left of each simplex.
+    pub fn boundary_chain(&self, mask: &[bool]) -> SimplicalChain<T> {
+        let mut simplices = Vec::new();
+        for (idx, t) in self.triangles() {
+            if !mask[idx.0] {
+                continue;
+            }
+            for i in 0..3 {
+                if let Some(n) = t.neighbors[i] {
+                    if mask[n.0] {
+                        continue;
+                    }
+                }
+                let sub = SubIdx(i);
+                simplices.push(Simplex {
+                    src: self.vert(t.vert(sub.cw())).clone(),
+                    dst: self.vert(t.vert(sub)).clone(),
+                });
+            }
+        }
+        SimplicalChain { simplices }
+    }
+
+    // squared circumradius of the triangle is less than `alpha_sq`
+    fn circumradius_lt(&self, idx: TriIdx, alpha_sq: &T) -> bool {
+        let [v0, v1, v2] = self.tri(idx).vertices;
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        let p2 = self.vert(v2);
+
+        // R^2 = |a|^2 |b|^2 |c|^2 / (4 * cross^2)
+        let cross = (p1.array[0].clone() - p0.array[0].clone())
+            * (p2.array[1].clone() - p0.array[1].clone())
+            - (p1.array[1].clone() - p0.array[1].clone())
+                * (p2.array[0].clone() - p0.array[0].clone());
+        let lhs = dist_sq(p0, p1) * dist_sq(p1, p2) * dist_sq(p2, p0);
+        let rhs = alpha_sq.clone() * T::from_constant(4) * cross.clone() * cross;
+        lhs < rhs
+    }
+
+    /// Alpha shape of non-super vertices: union of triangles with circumradius less than
+    /// `alpha`.
+    pub fn alpha_shape(&self, alpha: T) -> SimplicalChain<T> {
+        let alpha_sq = alpha.clone() * alpha;
+        let mask = (0..self.tri_len())
+            .map(|idx| {
+                let idx = TriIdx(idx);
+                !self.tri(idx).is_super() && self.circumradius_lt(idx, &alpha_sq)
+            })
+            .collect::<Vec<_>>();
+        self.boundary_chain(&mask)
+    }
+
+    /// Concave hull of non-super vertices. Starting from the convex hull, triangles are removed
+    /// through the longest boundary edge, while the edge is longer than `threshold` and the
+    /// boundary stays a simple polygon.
+    // http://www.geosensor.net/papers/duckham08.PR.pdf
+    pub fn concave_hull(&self, threshold: T) -> SimplicalChain<T> {
+        let threshold_sq = threshold.clone() * threshold;
+
+        let mut mask = vec![false; self.tri_len()];
+        for (idx, t) in self.triangles() {
+            mask[idx.0] = !t.is_super();
+        }
+        let mut on_boundary = vec![false; self.vertices.len()];
+        let mut queue = BinaryHeap::new();
+
+        let edge_len = |idx: TriIdx, sub: SubIdx| {
+            let t = self.tri(idx);
+            dist_sq(self.vert(t.vert(sub.cw())), self.vert(t.vert(sub)))
+        };
+
+        for (idx, t) in self.triangles() {
+            if !mask[idx.0] {
+                continue;
+            }
+            for i in 0..3 {
+                let outer = match t.neighbors[i] {
+                    Some(n) => !mask[n.0],
+                    None => true,
+                };
+                if !outer {
+                    continue;
+                }
+                let sub = SubIdx(i);
+                on_boundary[t.vert(sub).0] = true;
+                on_boundary[t.vert(sub.cw()).0] = true;
+
+                let len = edge_len(idx, sub);
+                if len > threshold_sq {
+                    queue.push(BoundaryEdge { len, tri: idx, sub });
+                }
+            }
+        }
+
+        while let Some(BoundaryEdge { tri, sub, .. }) = queue.pop() {
+            if !mask[tri.0] {
+                continue;
+            }
+
+            // removing the triangle should not touch the boundary at the opposite vertex
+            let t = self.tri(tri);
+            let v_opposite = t.vert(sub.ccw());
+            if on_boundary[v_opposite.0] {
+                continue;
+            }
+
+            mask[tri.0] = false;
+            on_boundary[v_opposite.0] = true;
+
+            for sub in [sub.ccw(), sub.cw()] {
+                let n = match t.neighbors[sub.0] {
+                    Some(n) => n,
+                    None => continue,
+                };
+                if !mask[n.0] {
+                    continue;
+                }
+                let n_sub = match self.tri(n).neighbors.iter().position(|n| *n == Some(tri)) {
+                    Some(i) => SubIdx(i),
+                    None => continue,
+                };
+                let len = edge_len(n, n_sub);
+                if len > threshold_sq {
+                    queue.push(BoundaryEdge {
+                        len,
+                        tri: n,
+                        sub: n_sub,
+                    });
+                }
+            }
+        }
+
+        self.boundary_chain(&mask)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::points_grid;
+    use rgeometry::data::*;
+
+    #[test]
+    fn alpha_shape_grid() {
+        let net = test_net(&points_grid(10.0, 5));
+
+        let sx = net.alpha_shape(4.0);
+        assert_eq!(sx.simplices.len(), 16);
+        assert_eq!(sx.characteristic(&Point::new([0.1, 0.2])), 1.0);
+        assert_eq!(sx.characteristic(&Point::new([11.0, 0.2])), 0.0);
+
+        let sx = net.alpha_shape(3.0);
+        assert!(sx.simplices.is_empty());
+    }
+
+    #[test]
+    fn concave_hull_dent() {
+        let net = test_net(&[
+            Point::new([0.0, 0.0]),
+            Point::new([12.0, 0.0]),
+            Point::new([11.0, 10.0]),
+            Point::new([1.0, 10.0]),
+            Point::new([6.0, 8.0]),
+        ]);
+
+        let convex = net.concave_hull(100.0);
+        assert_eq!(convex.simplices.len(), 4);
+        assert_eq!(convex.characteristic(&Point::new([6.0, 2.0])), 1.0);
+
+        let concave = net.concave_hull(11.0);
+        assert_eq!(concave.simplices.len(), 5);
+        assert_eq!(concave.characteristic(&Point::new([6.0, 2.0])), 0.0);
+        assert_eq!(concave.characteristic(&Point::new([6.0, 9.0])), 1.0);
+    }
+}
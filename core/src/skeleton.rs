@@ -0,0 +1,135 @@
+use crate::clearance::segment_dist_sq;
+use crate::delaunay::*;
+use rgeometry::data::*;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MedialAxisNode {
+    pub pos: Point<f64>,
+    /// clearance radius, distance to the nearest wall
+    pub radius: f64,
+}
+
+/// Approximate medial axis of the free space, as a graph
+#[derive(Debug, Clone, Default)]
+pub struct MedialAxis {
+    pub nodes: Vec<MedialAxisNode>,
+    /// pairs of indices into `nodes`
+    pub edges: Vec<(usize, usize)>,
+}
+
+fn midpoint(p0: &Point<f64>, p1: &Point<f64>) -> Point<f64> {
+    Point::new([
+        (p0.array[0] + p1.array[0]) / 2.0,
+        (p0.array[1] + p1.array[1]) / 2.0,
+    ])
+}
+
+impl TriangularNetwork<f64> {
+    // squared distance from `p` to vertices and closed sides of the triangle
+    fn tri_clearance_sq(&self, open: &[bool; 3], idx: TriIdx, p: &Point<f64>) -> f64 {
+        let t = self.tri(idx);
+        let mut d = f64::INFINITY;
+        for (i, open) in open.iter().enumerate() {
+            let sub = SubIdx(i);
+            let p_from = self.vert(t.vert(sub.cw()));
+            let p_to = self.vert(t.vert(sub));
+
+            d = d.min(p.squared_euclidean_distance(p_to));
+            if !open {
+                d = d.min(segment_dist_sq(p, p_from, p_to));
+            }
+        }
+        d
+    }
+
+    /// Approximate medial axis of the free space, using the chordal axis of the constrained
+    /// network. `edges` are sorted constraints, as returned by `build_net`. The free space
+    /// consists of non-super triangles outside of the constraints. Midpoints of open sides are
+    /// connected through the triangle: directly if the triangle has two open sides, and through
+    /// its centroid if it has one or three.
+    pub fn medial_axis(&self, edges: &[(VertIdx, VertIdx)]) -> MedialAxis {
+        let free = self.free_space(edges);
+
+        let mut out = MedialAxis::default();
+        let mut midpoints = BTreeMap::new();
+
+        for (idx, t) in self.triangles() {
+            if !free[idx.0] {
+                continue;
+            }
+            let open = [0, 1, 2].map(|i| self.side_open(edges, &free, idx, SubIdx(i)));
+
+            let mut mids = Vec::with_capacity(3);
+            for (i, _) in open.iter().enumerate().filter(|(_, open)| **open) {
+                let sub = SubIdx(i);
+                let v_from = t.vert(sub.cw());
+                let v_to = t.vert(sub);
+                let pos = midpoint(self.vert(v_from), self.vert(v_to));
+                let radius = self.tri_clearance_sq(&open, idx, &pos).sqrt();
+
+                let key = (v_from.min(v_to), v_from.max(v_to));
+                let node = *midpoints.entry(key).or_insert_with(|| {
+                    out.nodes.push(MedialAxisNode {
+                        pos,
+                        radius: f64::INFINITY,
+                    });
+                    out.nodes.len() - 1
+                });
+                let n = &mut out.nodes[node];
+                n.radius = n.radius.min(radius);
+                mids.push(node);
+            }
+
+            match mids.len() {
+                2 => out.edges.push((mids[0], mids[1])),
+                1 | 3 => {
+                    let pos = self.centroid(idx);
+                    let radius = self.tri_clearance_sq(&open, idx, &pos).sqrt();
+                    out.nodes.push(MedialAxisNode { pos, radius });
+                    let center = out.nodes.len() - 1;
+                    for m in mids {
+                        out.edges.push((center, m));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::boolean::SimplicalChain;
+    use crate::{build_net, Rect};
+
+    #[test]
+    fn medial_axis_corridor() {
+        // corridor between y = -2 and y = 2
+        let top = SimplicalChain::from_polygon(&Rect::new(5.0, 1.0).pos(0.0, 3.0).polygon(4));
+        let bottom = SimplicalChain::from_polygon(&Rect::new(5.0, 1.0).pos(0.0, -3.0).polygon(4));
+        let sx = top.union(&bottom);
+
+        let (net, c) = build_net(10.0, &sx, true);
+        let axis = net.medial_axis(&c);
+
+        // two terminal triangles at the ends, and sleeves between them
+        assert_eq!(axis.nodes.len(), 9);
+        assert_eq!(axis.edges.len(), 8);
+
+        let mids = axis
+            .nodes
+            .iter()
+            .filter(|n| n.pos.array[1] == 0.0)
+            .collect::<Vec<_>>();
+        assert_eq!(mids.len(), 7);
+        for n in mids {
+            // ends of the corridor are closed by the hull
+            let x = n.pos.array[0];
+            let expected = (5.0 - x.abs()).min(2.0);
+            assert!((n.radius - expected).abs() < 1e-9, "{:?}", n);
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use crate::boolean::{Simplex, SimplicalChain};
+use crate::delaunay::*;
+use crate::ConstrainedNet;
+use rgeometry::data::*;
+use std::collections::{HashMap, HashSet};
+
+type Result<T> = anyhow::Result<T>;
+
+/// How points are merged before they are inserted to `TriangularNetwork`. A distance that is not
+/// positive and finite merges equal points only, like `Exact`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Snap {
+    /// Merge equal points only. `-0.0` is equal to `0.0`.
+    Exact,
+    /// Merge a point to the first inserted point within the distance.
+    Epsilon(f64),
+    /// Round points to a grid with the spacing, and merge equal points.
+    Grid(f64),
+}
+
+impl Snap {
+    /// Position of the point after snapping. Only `Grid` moves points.
+    pub fn apply(&self, p: &Point<f64>) -> Point<f64> {
+        let [x, y] = p.array;
+        match (self, self.spacing()) {
+            (Snap::Grid(_), Some(spacing)) => Point::new([
+                (x / spacing).round() * spacing + 0.0,
+                (y / spacing).round() * spacing + 0.0,
+            ]),
+            // turns -0.0 into 0.0
+            _ => Point::new([x + 0.0, y + 0.0]),
+        }
+    }
+
+    /// Grid spacing for `SimplicalChain::snap_round`, or `None` for `Exact` and for distances
+    /// that are not positive and finite. `Epsilon` rounds to the power of two grid no finer than
+    /// the distance, so that pixel centers are exact.
+    pub fn spacing(&self) -> Option<f64> {
+        match *self {
+            Snap::Epsilon(eps) if eps > 0.0 && eps.is_finite() => {
+                Some(2f64.powi(eps.log2().ceil() as i32))
+            }
+            Snap::Grid(spacing) if spacing > 0.0 && spacing.is_finite() => Some(spacing),
+            _ => None,
+        }
+    }
+}
+
+fn exact_key(p: &Point<f64>) -> [u64; 2] {
+    p.array.map(f64::to_bits)
+}
+
+fn cell_key(p: &Point<f64>, eps: f64) -> [i64; 2] {
+    p.array.map(|v| (v / eps).floor() as i64)
+}
+
+// points in the network, hashed by `Snap`
+struct SnapIndex {
+    snap: Snap,
+    exact: HashMap<[u64; 2], VertIdx>,
+    cells: HashMap<[i64; 2], Vec<(Point<f64>, VertIdx)>>,
+}
+
+impl SnapIndex {
+    fn new(snap: Snap) -> Self {
+        Self {
+            snap: if snap.spacing().is_some() {
+                snap
+            } else {
+                Snap::Exact
+            },
+            exact: HashMap::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn find(&self, p: &Point<f64>) -> Option<VertIdx> {
+        if let Some(idx) = self.exact.get(&exact_key(p)) {
+            return Some(*idx);
+        }
+
+        let eps = match self.snap {
+            Snap::Epsilon(eps) => eps,
+            _ => return None,
+        };
+        let [x, y] = cell_key(p, eps);
+        let eps_sq = eps * eps;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let cell = match self.cells.get(&[x + dx, y + dy]) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+                for (q, idx) in cell {
+                    if p.squared_euclidean_distance::<f64>(q) <= eps_sq {
+                        return Some(*idx);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn add(&mut self, p: Point<f64>, idx: VertIdx) {
+        self.exact.insert(exact_key(&p), idx);
+        if let Snap::Epsilon(eps) = self.snap {
+            self.cells
+                .entry(cell_key(&p, eps))
+                .or_default()
+                .push((p, idx));
+        }
+    }
+}
+
+impl TriangularNetwork<f64> {
+    /// Insert points, merging near duplicates by `snap`. Existing vertices of the network are
+    /// merged too. Returns the vertex of each point, in the input order.
+    pub fn insert_snapped(
+        &mut self,
+        points: &[Point<f64>],
+        snap: Snap,
+        reductions: &mut usize,
+    ) -> Result<Vec<VertIdx>> {
+        let mut index = SnapIndex::new(snap);
+        for (idx, p) in self.vertices.iter().enumerate().skip(3) {
+            index.add(snap.apply(p), VertIdx(idx));
+        }
+
+        let mut mapping = Vec::with_capacity(points.len());
+        for p in points {
+            let p = snap.apply(p);
+            let idx = match index.find(&p) {
+                Some(idx) => idx,
+                None => {
+                    let idx = self.insert(&p, reductions)?;
+                    index.add(p, idx);
+                    idx
+                }
+            };
+            mapping.push(idx);
+        }
+        Ok(mapping)
+    }
+}
+
+// cell of the grid containing the point, by the index of its center
+fn pixel_key(p: &Point<f64>, spacing: f64) -> [i64; 2] {
+    p.array.map(|v| (v / spacing).round() as i64)
+}
+
+fn pixel_center(key: [i64; 2], spacing: f64) -> Point<f64> {
+    Point::new(key.map(|v| v as f64 * spacing + 0.0))
+}
+
+// centers of the hot pixels passed by `s` from its source, with `hot` sorted
+fn pixel_path(s: &Simplex<f64>, hot: &[[i64; 2]], spacing: f64) -> Vec<Point<f64>> {
+    let [x0, y0] = s.src.array;
+    let [x1, y1] = s.dst.array;
+    let [dx, dy] = [x1 - x0, y1 - y0];
+    let half = spacing / 2.0;
+
+    // pixels overlapping the bounding box along the x axis
+    let lo = (x0.min(x1) / spacing - 0.5).floor() as i64;
+    let hi = (x0.max(x1) / spacing + 0.5).ceil() as i64;
+    let start = hot.partition_point(|k| k[0] < lo);
+    let end = hot.partition_point(|k| k[0] <= hi);
+
+    let mut path = Vec::new();
+    for key in &hot[start..end] {
+        let [cx, cy] = pixel_center(*key, spacing).array;
+        if cx + half < x0.min(x1)
+            || x0.max(x1) < cx - half
+            || cy + half < y0.min(y1)
+            || y0.max(y1) < cy - half
+        {
+            continue;
+        }
+        // distance of the line from the center, against the extent of the pixel across the line
+        let cross = dx * (cy - y0) - dy * (cx - x0);
+        if cross.abs() > half * (dx.abs() + dy.abs()) {
+            continue;
+        }
+        let t = dx * (cx - x0) + dy * (cy - y0);
+        path.push((t, *key));
+    }
+    path.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    path.dedup_by_key(|(_, key)| *key);
+    path.into_iter()
+        .map(|(_, key)| pixel_center(key, spacing))
+        .collect()
+}
+
+impl SimplicalChain<f64> {
+    /// Snap round chains together to a grid with the spacing. Cells of the grid with an end
+    /// point or an intersection of simplices are hot pixels, and each simplex is replaced by a
+    /// path through the centers of the hot pixels it passes. Near coincident vertices end up in
+    /// the same pixel, and nearly collinear overlaps pass the same pixels, so simplices of the
+    /// result either meet at end points or are equal, and boolean operations between the chains
+    /// need no new points. Simplices collapsed to a point are dropped, and opposite simplices of
+    /// a chain cancel out.
+    pub fn snap_round(chains: &[SimplicalChain<f64>], spacing: f64) -> Vec<SimplicalChain<f64>> {
+        let all = SimplicalChain {
+            simplices: chains
+                .iter()
+                .flat_map(|sx| sx.simplices.iter().cloned())
+                .collect(),
+        };
+
+        let mut hot = all
+            .simplices
+            .iter()
+            .flat_map(|s| [&s.src, &s.dst])
+            .map(|p| pixel_key(p, spacing))
+            .collect::<Vec<_>>();
+        for (_, _, p) in all.subdivide_prepare(&all) {
+            hot.push(pixel_key(&p, spacing));
+        }
+        hot.sort();
+        hot.dedup();
+
+        chains
+            .iter()
+            .map(|sx| {
+                let mut simplices = Vec::new();
+                for s in &sx.simplices {
+                    let path = pixel_path(s, &hot, spacing);
+                    for pair in path.windows(2) {
+                        simplices.push(Simplex {
+                            src: pair[0],
+                            dst: pair[1],
+                        });
+                    }
+                }
+                SimplicalChain { simplices }.cancel_opposite()
+            })
+            .collect()
+    }
+}
+
+// insert points of `sx` to `net` snapped by `snap`, and constrain its simplices if `cut`. Stops
+// at the first failure, keeping the points and constraints added so far
+fn build_net_into(
+    net: &mut TriangularNetwork<f64>,
+    constraints: &mut Vec<(VertIdx, VertIdx)>,
+    sx: &SimplicalChain<f64>,
+    cut: bool,
+    snap: Snap,
+) -> Result<()> {
+    let points = sx
+        .simplices
+        .iter()
+        .flat_map(|s| [s.src, s.dst])
+        .collect::<Vec<_>>();
+    let mut r = usize::MAX;
+    let mapping = net.insert_snapped(&points, snap, &mut r)?;
+    if !cut {
+        return Ok(());
+    }
+
+    let mut result = Ok(());
+    let mut seen = HashSet::with_capacity(sx.simplices.len());
+    for edge in mapping.chunks(2) {
+        let (idx0, idx1) = (edge[0], edge[1]);
+        if idx0 == idx1 || !seen.insert((idx0, idx1)) {
+            continue;
+        }
+
+        if let Err(e) = net.constrain_edge(idx0, idx1) {
+            result = Err(e);
+            break;
+        }
+        constraints.push((idx0, idx1));
+    }
+    constraints.sort();
+    result
+}
+
+fn super_net(view: f64) -> TriangularNetwork<f64> {
+    let v = view * 4.0;
+    TriangularNetwork::new(
+        Point::new([-v, -v]),
+        Point::new([v, -v]),
+        Point::new([0.0, v]),
+    )
+}
+
+/// `build_net`, with points snapped by `snap`. Simplices whose endpoints are merged are not
+/// constrained.
+pub fn build_net_snapped(
+    view: f64,
+    sx: &SimplicalChain<f64>,
+    cut: bool,
+    snap: Snap,
+) -> (TriangularNetwork<f64>, Vec<(VertIdx, VertIdx)>) {
+    let mut net = super_net(view);
+    let mut constraints = Vec::with_capacity(sx.simplices.len());
+    if let Err(e) = build_net_into(&mut net, &mut constraints, sx, cut, snap) {
+        eprintln!("build_net: cut={:?}, e={:?}", cut, e);
+    }
+    (net, constraints)
+}
+
+/// `build_net_snapped`, but fails instead of returning a partial network
+pub fn try_build_net_snapped(
+    view: f64,
+    sx: &SimplicalChain<f64>,
+    cut: bool,
+    snap: Snap,
+) -> Result<ConstrainedNet> {
+    let mut net = super_net(view);
+    let mut constraints = Vec::with_capacity(sx.simplices.len());
+    build_net_into(&mut net, &mut constraints, sx, cut, snap)?;
+    Ok((net, constraints))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cross;
+
+    fn chain(rects: &[[f64; 4]]) -> SimplicalChain<f64> {
+        let mut sx = SimplicalChain::default();
+        for [x0, y0, x1, y1] in rects {
+            let points = [[*x0, *y0], [*x1, *y0], [*x1, *y1], [*x0, *y1]];
+            let p = Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap();
+            sx = sx.union(&SimplicalChain::from_polygon(&p));
+        }
+        sx
+    }
+
+    fn min_area(net: &TriangularNetwork<f64>) -> f64 {
+        net.triangles()
+            .filter(|(_, t)| !t.is_super())
+            .map(|(_, t)| {
+                let [p0, p1, p2] = t.vertices.map(|v| net.vert(v).array);
+                cross(
+                    [p1[0] - p0[0], p1[1] - p0[1]],
+                    [p2[0] - p0[0], p2[1] - p0[1]],
+                ) / 2.0
+            })
+            .fold(f64::MAX, f64::min)
+    }
+
+    #[test]
+    fn snap_exact() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        let points = [[0.0, 1.0], [-0.0, 1.0], [2.0, 3.0], [0.0, 1.0]].map(Point::new);
+        let mapping = net.insert_snapped(&points, Snap::Exact, &mut r).unwrap();
+        assert_eq!(
+            mapping,
+            vec![VertIdx(3), VertIdx(3), VertIdx(4), VertIdx(3)]
+        );
+        assert_eq!(net.vertices.len(), 5);
+    }
+
+    #[test]
+    fn snap_epsilon() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        let points = [[0.0, 0.0], [0.05, 0.0], [0.0, -0.08], [0.3, 0.0]].map(Point::new);
+        let mapping = net
+            .insert_snapped(&points, Snap::Epsilon(0.1), &mut r)
+            .unwrap();
+        assert_eq!(mapping[0], mapping[1]);
+        assert_eq!(mapping[0], mapping[2]);
+        assert_ne!(mapping[0], mapping[3]);
+
+        // existing vertices are merged too
+        let mapping2 = net
+            .insert_snapped(&[Point::new([0.32, 0.0])], Snap::Epsilon(0.1), &mut r)
+            .unwrap();
+        assert_eq!(mapping2, vec![mapping[3]]);
+        assert_eq!(net.vertices.len(), 5);
+    }
+
+    #[test]
+    fn snap_grid() {
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        let points = [[0.24, -0.01], [0.26, 0.01], [-0.1, 0.1]].map(Point::new);
+        let mapping = net
+            .insert_snapped(&points, Snap::Grid(0.5), &mut r)
+            .unwrap();
+        assert_eq!(mapping[0], mapping[2]);
+        assert_eq!(*net.vert(mapping[0]), Point::new([0.0, 0.0]));
+        assert_eq!(*net.vert(mapping[1]), Point::new([0.5, 0.0]));
+    }
+
+    #[test]
+    fn snap_invalid_distance() {
+        assert_eq!(Snap::Epsilon(0.1).spacing(), Some(0.125));
+        assert_eq!(Snap::Grid(0.1).spacing(), Some(0.1));
+        for d in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert_eq!(Snap::Epsilon(d).spacing(), None);
+            assert_eq!(Snap::Grid(d).spacing(), None);
+            let p = Point::new([0.3, -0.0]);
+            assert_eq!(Snap::Grid(d).apply(&p), Point::new([0.3, 0.0]));
+        }
+
+        // merges equal points only
+        let mut net = test_net(&[]);
+        let mut r = usize::MAX;
+        let points = [[0.0, 0.0], [0.05, 0.0], [0.0, 0.0]].map(Point::new);
+        let mapping = net
+            .insert_snapped(&points, Snap::Epsilon(-0.1), &mut r)
+            .unwrap();
+        assert_ne!(mapping[0], mapping[1]);
+        assert_eq!(mapping[0], mapping[2]);
+    }
+
+    #[test]
+    fn snap_round_sliver() {
+        let square = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            let points = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]];
+            let p = Polygon::new(points.iter().map(|p| Point::new(*p)).collect()).unwrap();
+            SimplicalChain::from_polygon(&p)
+        };
+        let spacing = 2f64.powi(-20);
+
+        // nearly touching squares share the side after rounding
+        let chains = [
+            square(0.0, 0.0, 1.0, 1.0),
+            square(1.0 + 1e-12, 1e-12, 2.0, 1.0),
+        ];
+        let [sx0, sx1]: [SimplicalChain<f64>; 2] = SimplicalChain::snap_round(&chains, spacing)
+            .try_into()
+            .unwrap();
+        let grid = Snap::Grid(spacing);
+        assert!(sx1
+            .simplices
+            .iter()
+            .all(|s| grid.apply(&s.src) == s.src && grid.apply(&s.dst) == s.dst));
+        let union = sx0.union(&sx1).cancel_opposite();
+        assert_eq!(union.rings().unwrap().len(), 1);
+        assert_eq!(union.signed_area(), 2.0);
+
+        // a sliver collapses
+        let chains = [square(0.0, 0.0, 1.0, 1e-12)];
+        assert!(SimplicalChain::snap_round(&chains, spacing)[0]
+            .simplices
+            .is_empty());
+
+        // a vertex close to a side splits the side
+        let chains = [
+            square(0.0, 0.0, 2.0, 1.0),
+            square(1.0, 1.0 + 1e-12, 1.5, 2.0),
+        ];
+        let snapped = SimplicalChain::snap_round(&chains, spacing);
+        assert_eq!(snapped[0].simplices.len(), 6);
+        let union = snapped[0].union(&snapped[1]).cancel_opposite();
+        assert_eq!(union.rings().unwrap().len(), 1);
+        assert_eq!(union.signed_area(), 2.5);
+    }
+
+    #[test]
+    fn build_net_sliver() {
+        // two squares, separated by a tiny gap
+        let sx = chain(&[[0.0, 0.0, 1.0, 1.0], [1.0 + 1e-9, 0.0, 2.0, 1.0]]);
+
+        let (net, c) = build_net_snapped(10.0, &sx, true, Snap::Exact);
+        assert_eq!(net.vertices.len(), 3 + 8);
+        assert_eq!(c.len(), 8);
+        assert!(min_area(&net) < 1e-6);
+
+        let (net, c) = build_net_snapped(10.0, &sx, true, Snap::Epsilon(1e-6));
+        assert_eq!(net.vertices.len(), 3 + 6);
+        assert_eq!(c.len(), 8);
+        assert!(min_area(&net) > 0.1);
+    }
+}
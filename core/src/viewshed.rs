@@ -0,0 +1,240 @@
+use crate::cross;
+use crate::delaunay::*;
+use rgeometry::data::*;
+use std::collections::VecDeque;
+
+/// Visibility of a triangle, as seen from the observer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewshedClass {
+    /// vertices and centroid of the triangle are visible
+    Visible,
+    /// some of vertices or centroid are visible
+    Partial,
+    /// none of vertices or centroid are visible
+    Hidden,
+}
+
+#[derive(Debug, Clone)]
+pub struct Viewshed {
+    pub origin: Point<f64>,
+    /// elevation of the observer's eye, ground elevation plus observer height
+    pub eye: f64,
+    /// visibility of vertices, indexed by `VertIdx`
+    pub vertices: Vec<Option<bool>>,
+    /// class of triangles, indexed by `TriIdx`. `None` for triangles not visited
+    pub classes: Vec<Option<ViewshedClass>>,
+}
+
+impl Viewshed {
+    pub fn class(&self, idx: TriIdx) -> Option<ViewshedClass> {
+        self.classes.get(idx.0).copied().flatten()
+    }
+
+    /// Visited triangles and their classes, in the order of `TriIdx`
+    pub fn triangles(&self) -> impl Iterator<Item = (TriIdx, ViewshedClass)> + '_ {
+        self.classes
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, class)| class.map(|class| (TriIdx(idx), class)))
+    }
+}
+
+// > 0 if `b` is counterclockwise of `o -> a`
+fn orient(o: &Point<f64>, a: &Point<f64>, b: &Point<f64>) -> f64 {
+    let [ox, oy] = o.array;
+    let [ax, ay] = a.array;
+    let [bx, by] = b.array;
+    cross([ax - ox, ay - oy], [bx - ox, by - oy])
+}
+
+impl TriangularNetwork<f64> {
+    /// Elevation of `p` on the plane of the triangle, interpolated from `elevations` of its
+    /// vertices.
+    pub fn elevation(&self, elevations: &[f64], tri: TriIdx, p: &Point<f64>) -> f64 {
+        let [v0, v1, v2] = self.tri(tri).vertices;
+        let p0 = self.vert(v0);
+        let p1 = self.vert(v1);
+        let p2 = self.vert(v2);
+
+        let area = orient(p0, p1, p2);
+        let w0 = orient(p1, p2, p) / area;
+        let w1 = orient(p2, p0, p) / area;
+        let w2 = orient(p0, p1, p) / area;
+
+        w0 * elevations[v0.0] + w1 * elevations[v1.0] + w2 * elevations[v2.0]
+    }
+
+    // walk triangles crossed by `p -> q`, and check that the terrain stays below the line of sight
+    fn line_of_sight(
+        &self,
+        elevations: &[f64],
+        start: TriIdx,
+        p: &Point<f64>,
+        eye: f64,
+        q: &Point<f64>,
+        target: f64,
+    ) -> bool {
+        let [px, py] = p.array;
+        let dx = q.array[0] - px;
+        let dy = q.array[1] - py;
+        let len_sq = dx * dx + dy * dy;
+        if len_sq == 0.0 {
+            return true;
+        }
+
+        let walk = self.walk_segment_from(&[], start, p, q);
+        for e in &walk.crossed {
+            let v_from = self.edge_from(e);
+            let v_to = self.edge_to(e);
+            let a = self.vert(v_from);
+            let b = self.vert(v_to);
+
+            let da = orient(p, q, a);
+            let db = orient(p, q, b);
+            let s = if da == db { 0.0 } else { da / (da - db) };
+            let x = a.array[0] + (b.array[0] - a.array[0]) * s;
+            let y = a.array[1] + (b.array[1] - a.array[1]) * s;
+            let ground = elevations[v_from.0] + (elevations[v_to.0] - elevations[v_from.0]) * s;
+
+            let ratio = ((x - px) * dx + (y - py) * dy) / len_sq;
+            let sight = eye + (target - eye) * ratio;
+            if ground > sight {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Compute a viewshed of an observer at `p`, standing `height` above the terrain.
+    /// `elevations` are elevations of vertices, indexed by `VertIdx`. Triangles are visited
+    /// outward from the observer's triangle, and classified by the visibility of their vertices
+    /// and centroid. Super triangles are not visited.
+    pub fn viewshed(&self, elevations: &[f64], p: &Point<f64>, height: f64) -> Option<Viewshed> {
+        use TriangularNetworkLocation::*;
+
+        if elevations.len() != self.vertices.len() {
+            return None;
+        }
+
+        let start = match self.locate_recursive(p) {
+            InTriangle(idx) => idx,
+            OnVertex(idx, _) => idx,
+            OnEdge(e) => e.tri(),
+            _ => return None,
+        };
+        if self.tri(start).is_super() {
+            return None;
+        }
+
+        let eye = self.elevation(elevations, start, p) + height;
+
+        let mut vertices = vec![None; self.vertices.len()];
+        let mut visited = vec![false; self.tri_len()];
+        let mut classes = vec![None; self.tri_len()];
+
+        let mut queue = VecDeque::new();
+        visited[start.0] = true;
+        queue.push_back(start);
+
+        while let Some(idx) = queue.pop_front() {
+            let t = self.tri(idx);
+
+            let mut visible = 0;
+            for v in t.vertices {
+                let v_visible = match vertices[v.0] {
+                    Some(v_visible) => v_visible,
+                    None => {
+                        let v_visible = self.line_of_sight(
+                            elevations,
+                            start,
+                            p,
+                            eye,
+                            self.vert(v),
+                            elevations[v.0],
+                        );
+                        vertices[v.0] = Some(v_visible);
+                        v_visible
+                    }
+                };
+                if v_visible {
+                    visible += 1;
+                }
+            }
+
+            let c = self.centroid(idx);
+            let c_elevation = self.elevation(elevations, idx, &c);
+            if self.line_of_sight(elevations, start, p, eye, &c, c_elevation) {
+                visible += 1;
+            }
+
+            let class = match visible {
+                4 => ViewshedClass::Visible,
+                0 => ViewshedClass::Hidden,
+                _ => ViewshedClass::Partial,
+            };
+            classes[idx.0] = Some(class);
+
+            for n in t.neighbors.iter().flatten() {
+                if visited[n.0] || self.tri(*n).is_super() {
+                    continue;
+                }
+                visited[n.0] = true;
+                queue.push_back(*n);
+            }
+        }
+
+        Some(Viewshed {
+            origin: *p,
+            eye,
+            vertices,
+            classes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::points_grid;
+
+    #[test]
+    fn viewshed_flat() {
+        let net = test_net(&points_grid(10.0, 5));
+        let elevations = vec![0.0; net.vertices.len()];
+
+        let vs = net
+            .viewshed(&elevations, &Point::new([-7.3, 1.1]), 1.0)
+            .unwrap();
+        assert_eq!(vs.eye, 1.0);
+
+        let count = net.triangles().filter(|(_, t)| !t.is_super()).count();
+        assert_eq!(vs.triangles().count(), count);
+        for (idx, class) in vs.triangles() {
+            assert_eq!(class, ViewshedClass::Visible);
+            assert_eq!(vs.class(idx), Some(class));
+        }
+    }
+
+    #[test]
+    fn viewshed_ridge() {
+        let net = test_net(&points_grid(10.0, 5));
+        // ridge along x = 0
+        let elevations = net
+            .vertices
+            .iter()
+            .map(|p| if p.array[0] == 0.0 { 10.0 } else { 0.0 })
+            .collect::<Vec<_>>();
+
+        let vs = net
+            .viewshed(&elevations, &Point::new([-7.3, 1.1]), 1.0)
+            .unwrap();
+
+        let near = net.find_vert(&Point::new([-10.0, 0.0])).unwrap();
+        let ridge = net.find_vert(&Point::new([0.0, 0.0])).unwrap();
+        let far = net.find_vert(&Point::new([10.0, 0.0])).unwrap();
+
+        assert_eq!(vs.vertices[near.0], Some(true));
+        assert_eq!(vs.vertices[ridge.0], Some(true));
+        assert_eq!(vs.vertices[far.0], Some(false));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::delaunay::*;
+use rgeometry::{data::*, Orientation, PolygonScalar};
+
+/// Triangles crossed by a segment, see `TriangularNetwork::walk_segment`
+#[derive(Debug, Clone, Default)]
+pub struct SegmentWalk {
+    /// triangles in the order from the start of the segment
+    pub triangles: Vec<TriIdx>,
+    /// edges crossed by the segment. `crossed[i]` is the side of `triangles[i]`, shared with
+    /// `triangles[i + 1]`
+    pub crossed: Vec<Edge>,
+    /// index into `crossed` of the first constrained edge
+    pub first_constraint: Option<usize>,
+}
+
+impl SegmentWalk {
+    /// Whether the segment crosses a constrained edge
+    pub fn blocked(&self) -> bool {
+        self.first_constraint.is_some()
+    }
+}
+
+impl<T: PolygonScalar> TriangularNetwork<T> {
+    /// Walk the triangles crossed by the segment from `p` to `q`. `edges` are sorted
+    /// constraints, as returned by `build_net`, in either direction. A segment passing through
+    /// a vertex crosses one of the edges at the vertex. The walk stops at the boundary of the
+    /// network. Returns `None` if `p` is outside of the network.
+    pub fn walk_segment(
+        &self,
+        edges: &[(VertIdx, VertIdx)],
+        p: &Point<T>,
+        q: &Point<T>,
+    ) -> Option<SegmentWalk> {
+        use TriangularNetworkLocation::*;
+
+        let start = match self.locate_recursive(p) {
+            InTriangle(idx) => idx,
+            OnEdge(e) => {
+                // start from the side of the edge towards `q`
+                let a = self.vert(self.edge_from(&e));
+                let b = self.vert(self.edge_to(&e));
+                let o = Point::orient_along_direction(a, Direction::Through(b), q);
+                match self.tri(e.tri()).neighbors[e.sub().0] {
+                    Some(n) if o == Orientation::ClockWise => n,
+                    _ => e.tri(),
+                }
+            }
+            OnVertex(idx, sub) => self.vertex_wedge(idx, sub, q),
+            _ => return None,
+        };
+        Some(self.walk_segment_from(edges, start, p, q))
+    }
+
+    // triangle around the vertex, whose wedge contains the direction to `q`
+    fn vertex_wedge(&self, start: TriIdx, start_sub: SubIdx, q: &Point<T>) -> TriIdx {
+        use Orientation::*;
+
+        let v = self.tri(start).vert(start_sub);
+        let p = self.vert(v);
+        let (mut idx, mut sub) = (start, start_sub);
+        loop {
+            let t = self.tri(idx);
+            let a = self.vert(t.vert(sub.ccw()));
+            let b = self.vert(t.vert(sub.cw()));
+            if Point::orient_along_direction(p, Direction::Through(a), q) != ClockWise
+                && Point::orient_along_direction(p, Direction::Through(b), q) != CounterClockWise
+            {
+                return idx;
+            }
+
+            // the next triangle counterclockwise around the vertex
+            idx = match t.neighbors[sub.0] {
+                Some(n) if n != start => n,
+                _ => return start,
+            };
+            let t = self.tri(idx);
+            sub = SubIdx(t.vertices.iter().position(|u| *u == v).unwrap());
+        }
+    }
+
+    /// Walk the segment from `p` to `q`, starting from the triangle `start` which contains `p`.
+    pub fn walk_segment_from(
+        &self,
+        edges: &[(VertIdx, VertIdx)],
+        start: TriIdx,
+        p: &Point<T>,
+        q: &Point<T>,
+    ) -> SegmentWalk {
+        use Orientation::*;
+
+        let mut walk = SegmentWalk::default();
+        if p == q {
+            walk.triangles.push(start);
+            return walk;
+        }
+
+        let mut cur = start;
+        let mut prev = None;
+        for _ in 0..self.tri_len() {
+            walk.triangles.push(cur);
+            let t = self.tri(cur);
+
+            let mut next = None;
+            for i in 0..3 {
+                let sub = SubIdx(i);
+                let v_from = t.vert(sub.cw());
+                let v_to = t.vert(sub);
+                let a = self.vert(v_from);
+                let b = self.vert(v_to);
+
+                // q is not beyond the edge
+                if Point::orient_along_direction(a, Direction::Through(b), q) != ClockWise {
+                    continue;
+                }
+                let n = match t.neighbors[i] {
+                    Some(n) => n,
+                    None => continue,
+                };
+                if Some(n) == prev {
+                    continue;
+                }
+
+                // the segment should pass between a and b
+                if Point::orient_along_direction(p, Direction::Through(q), a) == CounterClockWise
+                    || Point::orient_along_direction(p, Direction::Through(q), b) == ClockWise
+                {
+                    continue;
+                }
+
+                if walk.first_constraint.is_none()
+                    && (edges.binary_search(&(v_from, v_to)).is_ok()
+                        || edges.binary_search(&(v_to, v_from)).is_ok())
+                {
+                    walk.first_constraint = Some(walk.crossed.len());
+                }
+                walk.crossed.push(Edge::new(cur, sub));
+                next = Some(n);
+                break;
+            }
+
+            match next {
+                Some(n) => {
+                    prev = Some(cur);
+                    cur = n;
+                }
+                None => break,
+            }
+        }
+        walk
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::boolean::SimplicalChain;
+    use crate::{build_net, points_grid, Rect};
+
+    #[test]
+    fn walk_segment_grid() {
+        let view = 10.0;
+        let v = view * 4.0;
+        let mut net = TriangularNetwork::new(
+            Point::new([-v, -v]),
+            Point::new([v, -v]),
+            Point::new([0.0, v]),
+        );
+        let mut r = usize::MAX;
+        for p in points_grid(view, 5) {
+            net.insert(&p, &mut r).unwrap();
+        }
+
+        let p = Point::new([-7.3, 1.1]);
+        let q = Point::new([8.1, -2.4]);
+        let walk = net.walk_segment(&[], &p, &q).unwrap();
+        assert!(!walk.blocked());
+        assert_eq!(walk.crossed.len() + 1, walk.triangles.len());
+        assert!(walk.crossed.len() >= 4);
+
+        // consecutive triangles share the crossed edges
+        for (i, e) in walk.crossed.iter().enumerate() {
+            assert_eq!(e.tri(), walk.triangles[i]);
+            let t = net.tri(walk.triangles[i]);
+            assert_eq!(t.neighbors[e.sub().0], Some(walk.triangles[i + 1]));
+        }
+
+        let last = *walk.triangles.last().unwrap();
+        assert_eq!(
+            net.locate(last, &q),
+            TriangularNetworkLocation::InTriangle(last)
+        );
+
+        // from a vertex, into the wedge towards q
+        let o = Point::new([0.0, 0.0]);
+        let q = Point::new([3.0, 1.0]);
+        let walk = net.walk_segment(&[], &o, &q).unwrap();
+        let first = net.tri(walk.triangles[0]);
+        assert!(first.vertices.iter().any(|v| *net.vert(*v) == o));
+        let last = *walk.triangles.last().unwrap();
+        assert!(matches!(
+            net.locate(last, &q),
+            TriangularNetworkLocation::InTriangle(_) | TriangularNetworkLocation::OnEdge(_)
+        ));
+    }
+
+    #[test]
+    fn walk_segment_constrained() {
+        let rect = Rect::new(1.0, 1.0).pos(0.0, 0.0);
+        let sx = SimplicalChain::from_polygon(&rect.polygon(1));
+        let (net, c) = build_net(10.0, &sx, true);
+
+        let walk = net
+            .walk_segment(&c, &Point::new([-5.0, 0.2]), &Point::new([5.0, 0.3]))
+            .unwrap();
+        assert!(walk.blocked());
+        let e = walk.crossed[walk.first_constraint.unwrap()];
+        assert_eq!(net.vert(net.edge_from(&e)).array[0], -1.0);
+
+        let walk = net
+            .walk_segment(&c, &Point::new([-5.0, 2.0]), &Point::new([5.0, 2.0]))
+            .unwrap();
+        assert!(!walk.blocked());
+
+        let walk = net
+            .walk_segment(&c, &Point::new([-0.5, 0.2]), &Point::new([0.5, -0.3]))
+            .unwrap();
+        assert!(!walk.blocked());
+    }
+}
@@ -0,0 +1,48 @@
+mod tests {
+    use core::boolean::*;
+    use core::snap::Snap;
+    use core::*;
+    use num::{BigRational, FromPrimitive, ToPrimitive};
+    use rgeometry::data::*;
+
+    // exact, since rounding the union to f64 may leave spikes
+    fn rational_union(rects: &[Rect]) -> SimplicalChain<BigRational> {
+        SimplicalChain::union_all(rects.iter().map(|r| {
+            let points = r
+                .polygon(1)
+                .iter()
+                .map(|p| p.map(|v| BigRational::from_f64(v).unwrap()))
+                .collect::<Vec<_>>();
+            SimplicalChain::from_polygon(&Polygon::new_unchecked(points))
+        }))
+    }
+
+    #[test]
+    fn snap_round_rects() {
+        let rects = fixtures::boolean2_rects();
+        let expected = rational_union(&rects);
+
+        let chains = rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(1)))
+            .collect::<Vec<_>>();
+        let spacing = Snap::Epsilon(1e-9).spacing().unwrap();
+        let sx = SimplicalChain::union_all(SimplicalChain::snap_round(&chains, spacing));
+
+        let expected_area = expected.signed_area().to_f64().unwrap();
+        assert!((sx.signed_area() - expected_area).abs() < 1e-6);
+        assert_eq!(sx.rings().unwrap().len(), expected.rings().unwrap().len());
+        assert_eq!(
+            sx.polygons().unwrap().len(),
+            expected.polygons().unwrap().len()
+        );
+
+        // with a circle, as in the demo
+        let circle =
+            SimplicalChain::from_polygon(&Polygon::new(points_circular(100.0, 32)).unwrap());
+        for op in [BooleanOp::Union, BooleanOp::Difference, BooleanOp::Xor] {
+            let out = sx.boolean_snapped(&circle, op, Snap::Epsilon(1e-9));
+            assert!(out.polygons().is_ok(), "{:?}", op);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod tests {
-    use core::boolean::SimplicalChain;
+    use core::boolean::{Simplex, SimplicalChain};
+    use core::delaunay::TriangularNetwork;
     use core::*;
     use rand::prelude::*;
     use rand_chacha::*;
@@ -36,6 +37,41 @@ mod tests {
         test_build_visibility(view, &rects);
     }
 
+    #[test]
+    fn build_net_open_chain() {
+        // an open polyline: the first point is the dst of no simplex
+        let points = [[-5.0, 0.0], [0.0, 2.0], [5.0, 0.0]].map(Point::new);
+        let mut simplices = points
+            .windows(2)
+            .map(|w| Simplex {
+                src: w[0],
+                dst: w[1],
+            })
+            .collect::<Vec<_>>();
+        // a repeated simplex, and a degenerate one through -0.0
+        simplices.push(simplices[0].clone());
+        simplices.push(Simplex {
+            src: points[1],
+            dst: Point::new([-0.0, 2.0]),
+        });
+        let sx = SimplicalChain { simplices };
+
+        let (net, c) = build_net(10.0, &sx, true);
+        assert_eq!(net.vertices.len(), 3 + points.len());
+        for p in &points {
+            assert!(net.find_vert(p).is_some());
+        }
+
+        let v = points.map(|p| net.find_vert(&p).unwrap());
+        let mut expected = vec![(v[0], v[1]), (v[1], v[2])];
+        expected.sort();
+        assert_eq!(c, expected);
+        let edges = net.edges();
+        for (v0, v1) in c {
+            assert!(edges.contains(&(v0.min(v1), v0.max(v1))));
+        }
+    }
+
     fn test_build_visibility(view: f64, rects: &[Rect]) {
         let mut sx = SimplicalChain::default();
         for r in rects {
@@ -47,5 +83,36 @@ mod tests {
         let (net, c) = build_net(view, &sx, true);
 
         let _vis = net.visibility(&c, &Point::new([0.0, 0.0]));
+
+        // same constraints, on a network built by divide-and-conquer
+        let v = view * 4.0;
+        let points = sx.simplices.iter().map(|s| s.dst).collect::<Vec<_>>();
+        let (mut net_dc, _) = TriangularNetwork::from_points(
+            Point::new([-v, -v]),
+            Point::new([v, -v]),
+            Point::new([0.0, v]),
+            &points,
+        )
+        .unwrap();
+        assert_eq!(net_dc.vertices.len(), net.vertices.len());
+        let mut c_dc = Vec::with_capacity(c.len());
+        for (v0, v1) in &c {
+            let idx0 = net_dc.find_vert(net.vert(*v0)).unwrap();
+            let idx1 = net_dc.find_vert(net.vert(*v1)).unwrap();
+            net_dc.constrain_edge(idx0, idx1).unwrap();
+            c_dc.push((idx0, idx1));
+        }
+        c_dc.sort();
+
+        let _vis = net_dc.visibility(&c_dc, &Point::new([0.0, 0.0]));
+
+        // the same obstacles, merged in a balanced tree
+        let sx_all = SimplicalChain::union_all(
+            rects
+                .iter()
+                .map(|r| SimplicalChain::from_polygon(&r.polygon(1))),
+        );
+        let (net_all, c_all) = build_net(view, &sx_all, true);
+        let _vis = net_all.visibility(&c_all, &Point::new([0.0, 0.0]));
     }
 }
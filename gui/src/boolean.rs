@@ -29,13 +29,11 @@ pub struct DemoBoolean {
 }
 
 fn rect_union(rects: &[Rect]) -> SimplicalChain<f64> {
-    let mut sx = SimplicalChain::default();
-    for r in rects {
-        let p = r.polygon(1);
-        let sx_r = SimplicalChain::from_polygon(&p);
-        sx = sx.union(&sx_r);
-    }
-    sx
+    SimplicalChain::union_all(
+        rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(1))),
+    )
 }
 
 impl DemoBoolean {
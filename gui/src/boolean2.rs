@@ -1,154 +1,19 @@
 use super::{p_rg_to_egui, plot_line, pt_egui, Demo};
 use core::boolean::*;
+use core::fixtures::boolean2_rects;
+use core::snap::Snap;
 use core::{gen_rects, points_circular, Rect};
 use eframe::egui::{self, epaint::Color32, Ui};
 use egui_plot::{self, *};
 use rgeometry::data::{Point, Polygon};
 
-const RECTS: &[[f64; 5]] = &[
-    [
-        -123.54193216787064,
-        -52.91399622244809,
-        7.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        -17.937753007736486,
-        -98.44154111468106,
-        82.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        42.874641733514906,
-        93.13573479724495,
-        102.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        -88.54424874680433,
-        40.89518325993956,
-        2.5,
-        102.5,
-        -0.4070386786719689,
-    ],
-    [
-        95.11519327082028,
-        -38.283155683074284,
-        2.5,
-        102.5,
-        -0.4070386786719689,
-    ],
-    [
-        9.611163294907207,
-        -110.31829195613312,
-        52.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        49.200332766414135,
-        -18.488570947320827,
-        52.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        -34.3242387359236,
-        -85.93222116993906,
-        2.5,
-        7.5,
-        -0.4070386786719689,
-    ],
-    [
-        -8.591278579444097,
-        -26.242902514211057,
-        2.5,
-        32.5,
-        -0.4070386786719689,
-    ],
-    [
-        75.32060853506682,
-        -84.19801618748045,
-        2.5,
-        52.5,
-        -0.4070386786719689,
-    ],
-    [
-        49.200332766414135,
-        -18.488570947320827,
-        52.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        88.78950223792106,
-        73.34115006149148,
-        52.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        5.264930735583327,
-        5.89749983887325,
-        2.5,
-        7.5,
-        -0.4070386786719689,
-    ],
-    [
-        30.997890892062827,
-        65.58681849460125,
-        2.5,
-        32.5,
-        -0.4070386786719689,
-    ],
-    [
-        114.90977800657376,
-        7.631704821331866,
-        2.5,
-        52.5,
-        -0.4070386786719689,
-    ],
-    [
-        -105.17598796610817,
-        -60.83183011674947,
-        17.5,
-        2.5,
-        -0.4070386786719689,
-    ],
-    [
-        -26.406404841622212,
-        -67.56627696817658,
-        2.5,
-        17.5,
-        -0.4070386786719689,
-    ],
-    [
-        13.182764629884712,
-        24.263444040635708,
-        2.5,
-        17.5,
-        -0.4070386786719689,
-    ],
-    [141.74915755470903, 100.0, 5.0, 5.0, -0.616137379020819],
-];
-
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum CircleMode {
-    Disabled,
-    Union,
-    Intersect,
-    Subtract0,
-    Subtract1,
-}
+const SNAP: Snap = Snap::Epsilon(1e-9);
 
 pub struct DemoBoolean2 {
     opt_render_rect: bool,
     opt_render_union: bool,
 
-    opt_circle_mode: CircleMode,
+    opt_circle_mode: Option<BooleanOp>,
 
     circle: Vec<Point<f64>>,
 
@@ -161,31 +26,30 @@ pub struct DemoBoolean2 {
 
     rational: bool,
     shake: bool,
+    snap: bool,
 }
 
-fn rect_union(rects: &[Rect], subdivide: usize, rational: bool) -> SimplicalChain<f64> {
+fn rect_union(rects: &[Rect], subdivide: usize, rational: bool, snap: bool) -> SimplicalChain<f64> {
     if !rational {
-        let mut sx = SimplicalChain::default();
-        for r in rects {
-            let p = r.polygon(subdivide);
-            let sx_r = SimplicalChain::from_polygon(&p);
-            sx = sx.union(&sx_r);
+        let mut chains = rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(subdivide)))
+            .collect::<Vec<_>>();
+        if snap {
+            chains = SimplicalChain::snap_round(&chains, SNAP.spacing().unwrap());
         }
-        sx
+        SimplicalChain::union_all(chains)
     } else {
         use num::{FromPrimitive, ToPrimitive};
 
-        let mut sx = SimplicalChain::default();
-        for r in rects {
+        let sx = SimplicalChain::union_all(rects.iter().map(|r| {
             let p = r.polygon(subdivide);
             let points = p
                 .iter_boundary()
                 .map(|p| p.map(|v| num::BigRational::from_f64(v).unwrap()))
                 .collect::<Vec<_>>();
-            let p = Polygon::new_unchecked(points);
-            let sx_r = SimplicalChain::from_polygon(&p);
-            sx = sx.union(&sx_r);
-        }
+            SimplicalChain::from_polygon(&Polygon::new_unchecked(points))
+        }));
 
         SimplicalChain {
             simplices: sx
@@ -200,14 +64,6 @@ fn rect_union(rects: &[Rect], subdivide: usize, rational: bool) -> SimplicalChai
     }
 }
 
-fn get_rects() -> Vec<Rect> {
-    let rects = RECTS
-        .iter()
-        .map(|[x, y, w, h, heading]| Rect::new(*w, *h).pos(*x, *y).rot(*heading))
-        .collect::<Vec<_>>();
-    rects
-}
-
 fn maybe_shake(rects: &[Rect], shake: bool) -> Vec<Rect> {
     let half = 1e-6;
     let mut rects = rects.iter().cloned().collect::<Vec<_>>();
@@ -225,18 +81,19 @@ impl DemoBoolean2 {
     pub fn new(view: f64) -> Self {
         let mut rng = rand::thread_rng();
 
-        let shake = true;
+        let shake = false;
         let rational = false;
+        let snap = true;
         let subdivide = 1;
 
-        let rects = get_rects();
-        let sx = rect_union(&maybe_shake(&rects, shake), subdivide, rational);
+        let rects = boolean2_rects();
+        let sx = rect_union(&maybe_shake(&rects, shake), subdivide, rational, snap);
 
         Self {
             opt_render_rect: true,
             opt_render_union: true,
 
-            opt_circle_mode: CircleMode::Disabled,
+            opt_circle_mode: None,
 
             circle: points_circular(view / 2.0, 32),
 
@@ -249,6 +106,7 @@ impl DemoBoolean2 {
 
             rational,
             shake,
+            snap,
         }
     }
 }
@@ -267,11 +125,12 @@ impl Demo for DemoBoolean2 {
             ui.separator();
 
             for (mode, label) in &[
-                (CircleMode::Disabled, "none"),
-                (CircleMode::Union, "union"),
-                (CircleMode::Intersect, "intersect"),
-                (CircleMode::Subtract0, "subtract0"),
-                (CircleMode::Subtract1, "subtract1"),
+                (None, "none"),
+                (Some(BooleanOp::Union), "union"),
+                (Some(BooleanOp::Intersection), "intersect"),
+                (Some(BooleanOp::Difference), "subtract0"),
+                (Some(BooleanOp::ReverseDifference), "subtract1"),
+                (Some(BooleanOp::Xor), "xor"),
             ] {
                 ui.radio_value(&mut self.opt_circle_mode, *mode, *label);
             }
@@ -283,6 +142,7 @@ impl Demo for DemoBoolean2 {
 
             ui.checkbox(&mut self.rational, "rational");
             ui.checkbox(&mut self.shake, "shake");
+            ui.checkbox(&mut self.snap, "snap");
             ui.separator();
 
             ui.add(egui::Slider::new(&mut self.count, 0..=self.rects.len()).text("counts"));
@@ -293,30 +153,18 @@ impl Demo for DemoBoolean2 {
             &maybe_shake(&self.rects[..self.count], self.shake),
             self.subdivide,
             self.rational,
+            self.snap,
         );
 
-        if self.opt_circle_mode == CircleMode::Disabled {
-            return;
-        }
+        let op = match self.opt_circle_mode {
+            Some(op) => op,
+            None => return,
+        };
 
         let p = Polygon::new(self.circle.clone()).unwrap();
         let sx_circle = SimplicalChain::from_polygon(&p);
-
-        match self.opt_circle_mode {
-            CircleMode::Union => {
-                self.sx = self.sx.union(&sx_circle);
-            }
-            CircleMode::Intersect => {
-                self.sx = self.sx.intersect(&sx_circle);
-            }
-            CircleMode::Subtract0 => {
-                self.sx = self.sx.subtract(&sx_circle);
-            }
-            CircleMode::Subtract1 => {
-                self.sx = sx_circle.subtract(&self.sx);
-            }
-            _ => (),
-        }
+        let snap = if self.snap { SNAP } else { Snap::Exact };
+        self.sx = self.sx.boolean_snapped(&sx_circle, op, snap);
     }
 
     fn plot_ui(&self, plot_ui: &mut PlotUi) {
@@ -2,7 +2,7 @@ use super::{p_rg_to_egui, plot_line, pt_egui, Demo};
 use core::{
     boolean::*,
     build_net,
-    delaunay::{TriIdx, TriangularNetwork, VertIdx},
+    delaunay::{TriangularNetwork, VertIdx},
     gen_rects, points_uniform,
     visibility::VisibilityResult,
     visibility_limit, Rect,
@@ -12,13 +12,11 @@ use egui_plot::{self, *};
 use rgeometry::data::Point;
 
 fn rect_union(rects: &[Rect]) -> SimplicalChain<f64> {
-    let mut sx = SimplicalChain::default();
-    for r in rects {
-        let p = r.polygon(1);
-        let sx_r = SimplicalChain::from_polygon(&p);
-        sx = sx.union(&sx_r);
-    }
-    sx
+    SimplicalChain::union_all(
+        rects
+            .iter()
+            .map(|r| SimplicalChain::from_polygon(&r.polygon(1))),
+    )
 }
 
 fn plot_net_inner(
@@ -27,12 +25,12 @@ fn plot_net_inner(
     plot_ui: &mut PlotUi,
     prune: bool,
 ) {
-    for (_t_idx, t) in net.triangles.iter().enumerate() {
+    for (_t_idx, t) in net.triangles() {
         let [v0, v1, v2] = t.vertices;
         let p0 = net.vert(v0);
         let p1 = net.vert(v1);
         let p2 = net.vert(v2);
-        let center = net.centroid(TriIdx(_t_idx));
+        let center = net.centroid(_t_idx);
 
         if prune && sx.characteristic(&center) != 1.0 {
             continue;
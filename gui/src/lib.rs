@@ -29,7 +29,7 @@ pub fn plot_polygon(plot_ui: &mut PlotUi, p: &Polygon<f64>, color: Color32) {
 }
 
 fn plot_net(net: &TriangularNetwork<f64>, plot_ui: &mut PlotUi, render_supertri: bool) {
-    for (_t_idx, t) in net.triangles.iter().enumerate() {
+    for (_t_idx, t) in net.triangles() {
         let [v0, v1, v2] = t.vertices;
         let p0 = net.vert(v0);
         let p1 = net.vert(v1);
@@ -1,6 +1,6 @@
 use core::{
     aabb::AABB,
-    boolean::SimplicalChain,
+    boolean::{self, SimplicalChain},
     delaunay::{TriangularNetwork, VertIdx},
     *,
 };
@@ -25,6 +25,27 @@ pub enum FillOp {
     Subtract,
 }
 
+#[wasm_bindgen]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    Difference,
+    ReverseDifference,
+    Xor,
+}
+
+impl From<BooleanOp> for boolean::BooleanOp {
+    fn from(op: BooleanOp) -> Self {
+        match op {
+            BooleanOp::Union => Self::Union,
+            BooleanOp::Intersection => Self::Intersection,
+            BooleanOp::Difference => Self::Difference,
+            BooleanOp::ReverseDifference => Self::ReverseDifference,
+            BooleanOp::Xor => Self::Xor,
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl Simplical {
     pub fn new() -> Self {
@@ -99,6 +120,18 @@ impl Simplical {
             sx: self.sx.subtract(&other.sx),
         }
     }
+
+    #[wasm_bindgen]
+    pub fn xor(&self, other: &Simplical) -> Self {
+        self.boolean(other, BooleanOp::Xor)
+    }
+
+    #[wasm_bindgen]
+    pub fn boolean(&self, other: &Simplical, op: BooleanOp) -> Self {
+        Self {
+            sx: self.sx.boolean(&other.sx, op.into()),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -135,9 +168,11 @@ impl Delaunay {
         Self { net }
     }
 
-    pub fn neighbors(&self) -> js_sys::Uint16Array {
-        let mut v = Vec::with_capacity(self.net.triangles.len() * 6);
-        for tri in &self.net.triangles {
+    /// Pairs of neighboring vertices, without the super triangle. Indices are u32, they were
+    /// u16 before and wrapped past 65536 points.
+    pub fn neighbors(&self) -> js_sys::Uint32Array {
+        let mut v = Vec::with_capacity(self.net.tri_len() * 6);
+        for (_, tri) in self.net.triangles() {
             for i in 0..3 {
                 let v0 = tri.vertices[i];
                 let v1 = tri.vertices[(i + 1) % 3];
@@ -145,11 +180,11 @@ impl Delaunay {
                     continue;
                 }
 
-                v.push(v0.0 as u16 - 3);
-                v.push(v1.0 as u16 - 3);
+                v.push(v0.0 as u32 - 3);
+                v.push(v1.0 as u32 - 3);
             }
         }
-        js_sys::Uint16Array::from(&v[..])
+        js_sys::Uint32Array::from(&v[..])
     }
 }
 